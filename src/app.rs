@@ -1,54 +1,125 @@
-use crate::config::{ConfigManager, ExtendLayout};
+use crate::backend::{DisplayBackend, confirming_backend, default_backend};
+use crate::config::{ConfigManager, ExtendAlignment, ExtendLayout, MonitorConfiguration};
 use crate::display::{
-    apply_extend_mode, apply_mirror_mode, apply_single_screen_mode, get_monitor_available_modes,
-    reset_to_defaults,
+    ROTATIONS, apply_display_mode, apply_extend_mode, capture_current_layout,
+    default_extend_configuration, mirror_sources, reset_to_defaults, restore_monitor_state,
+};
+use crate::identify;
+use crate::import;
+use crate::ipc;
+use crate::monitor_rule::format_scale;
+use crate::state::{
+    ConfirmResume, DisplayMode, FailedAction, Message, MonitorEvent, MonitorSettingsData,
+    MonitorSettingsEntry, PendingProfile, State,
+};
+use crate::ui::{
+    self, EditorMonitor, LayoutEditor, create_display_card_with_selection,
+    create_extend_card_with_selection,
 };
-use crate::state::{DisplayMode, Message, MonitorSettingsData, State};
-use crate::ui::{self, create_display_card_with_selection, create_extend_card_with_selection};
 
 use anyhow::Result;
-use hyprland::data::{Monitor, Monitors};
-use hyprland::shared::HyprData;
-use iced::widget::{Space, button, column, container, pick_list, row, text};
-use iced::{Element, Event, Length, Padding, Task, Theme, alignment, event, keyboard};
+use hyprland::data::Monitor;
+use iced::widget::{
+    Column, Space, button, canvas, column, container, pick_list, row, text, text_input,
+};
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use iced::{
+    Element, Event, Length, Padding, Task, Theme, alignment, event, keyboard, stream, time,
+};
 use iced_layershell::Application;
 use iced_layershell::settings::Settings;
 use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Seconds before an unconfirmed display change is reverted
+const REVERT_TIMEOUT_SECS: u64 = 15;
+
+/// Environment variable carrying a pending confirmation, as JSON, to a
+/// switcher restarted on another output
+const RESUME_ENV: &str = "DISPLAY_SWITCHER_RESUME";
+
+/// How the switcher was started
+#[derive(Debug, Default)]
+pub struct LaunchOptions {
+    /// Output the switcher is shown on, `None` if the compositor picks it
+    pub output: Option<String>,
+    /// Confirmation left pending by a switcher whose output went away
+    pub resume: Option<ConfirmResume>,
+}
+
+impl LaunchOptions {
+    /// Shows the switcher on the configured output if it is connected, and on
+    /// the focused monitor otherwise
+    pub fn detect() -> Self {
+        let monitors = default_backend().monitors().unwrap_or_default();
+        let configured = ConfigManager::new()
+            .ok()
+            .and_then(|config_manager| config_manager.get_switcher_output().map(str::to_string));
+
+        let output = configured
+            .filter(|name| monitors.iter().any(|m| &m.name == name))
+            .or_else(|| monitors.iter().find(|m| m.focused).map(|m| m.name.clone()));
+
+        let resume = std::env::var(RESUME_ENV).ok().and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| eprintln!("Ignoring invalid {RESUME_ENV}: {e}"))
+                .ok()
+        });
+
+        Self { output, resume }
+    }
+}
 
 pub struct DisplaySwitcher {
     state: State,
     config_manager: ConfigManager,
+    backend: Arc<dyn DisplayBackend>,
+    /// Overlay processes labelling each output, see `identify`
+    identify_overlays: Vec<process::Child>,
+    /// Output the switcher is shown on
+    output: Option<String>,
 }
 
 impl Application for DisplaySwitcher {
     type Message = Message;
-    type Flags = ();
+    type Flags = LaunchOptions;
     type Theme = Theme;
     type Executor = iced::executor::Default;
 
-    fn new(_flags: ()) -> (Self, Task<Message>) {
-        let app = match Self::new() {
+    fn new(options: LaunchOptions) -> (Self, Task<Message>) {
+        let backend = confirming_backend();
+        let mut app = match Self::new(backend.clone()) {
             Ok(app) => app,
             Err(e) => {
                 eprintln!("Failed to initialize application: {e}");
                 Self {
-                    state: State::Error { message: e },
+                    state: State::Error {
+                        message: e,
+                        failed_action: None,
+                    },
                     config_manager: ConfigManager::new().unwrap_or_default(),
+                    backend,
+                    identify_overlays: Vec::new(),
+                    output: None,
                 }
             }
         };
+        app.output = options.output;
 
-        (
-            app,
-            Task::perform(
-                async {
-                    Monitors::get()
-                        .map(|monitors| monitors.into_iter().collect::<Vec<Monitor>>())
-                        .map_err(|e| e.to_string())
-                },
-                Message::MonitorsLoaded,
-            ),
-        )
+        if let Some(resume) = options.resume {
+            app.state = State::ConfirmChanges {
+                previous_monitors: resume.previous_monitors,
+                previous_mirrors: resume.previous_mirrors,
+                pending_profile: resume.pending_profile,
+                remaining_seconds: resume.remaining_seconds,
+            };
+            return (app, Task::none());
+        }
+
+        let task = app.load_monitors();
+        (app, task)
     }
 
     fn namespace(&self) -> String {
@@ -57,15 +128,9 @@ impl Application for DisplaySwitcher {
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::LoadMonitors => Task::perform(
-                async {
-                    Monitors::get()
-                        .map(|monitors| monitors.into_iter().collect::<Vec<Monitor>>())
-                        .map_err(|e| e.to_string())
-                },
-                Message::MonitorsLoaded,
-            ),
+            Message::LoadMonitors => self.load_monitors(),
             Message::MonitorsLoaded(Ok(monitors)) => {
+                import::import_on_first_run(&mut self.config_manager, &monitors);
                 self.state = State::Loaded { 
                     monitors,
                     selected_index: 0,
@@ -75,62 +140,30 @@ impl Application for DisplaySwitcher {
             Message::MonitorsLoaded(Err(err)) => {
                 self.state = State::Error {
                     message: err.to_string(),
+                    failed_action: None,
                 };
                 Task::none()
             }
+            Message::MonitorsRefreshed(Ok(refreshed)) => {
+                // Only the list changes, the highlighted card stays put
+                if let State::Loaded { monitors, .. } = &mut self.state {
+                    *monitors = refreshed;
+                }
+                Task::none()
+            }
+            Message::MonitorsRefreshed(Err(err)) => {
+                eprintln!("Failed to refresh monitors: {err}");
+                Task::none()
+            }
             Message::SetMode(mode) => {
                 if let State::Loaded { monitors, .. } = &self.state {
-                    let result = match mode {
-                        DisplayMode::Mirror => apply_mirror_mode(monitors, &self.config_manager),
-                        DisplayMode::Extend => {
-                            let monitor_names: Vec<String> =
-                                monitors.iter().map(|m| m.name.clone()).collect();
-                            if let Some(saved_config) = self
-                                .config_manager
-                                .get_extend_configuration_for_monitors(&monitor_names)
-                            {
-                                apply_extend_mode(monitors, saved_config)
-                            } else if monitors.len() >= 2 {
-                                // Use the determined primary monitor instead of hardcoded array index
-                                let primary_monitor = crate::display::determine_primary_monitor(
-                                    monitors,
-                                    &self.config_manager,
-                                );
-                                let secondary_monitor = monitors
-                                    .iter()
-                                    .find(|m| m.name != primary_monitor.name)
-                                    .unwrap();
-
-                                let default_config = ConfigManager::create_config_from_settings(
-                                    primary_monitor.name.clone(),
-                                    secondary_monitor.name.clone(),
-                                    format!("{}x{}", primary_monitor.width, primary_monitor.height),
-                                    "normal".to_string(),
-                                    format!(
-                                        "{}x{}",
-                                        secondary_monitor.width, secondary_monitor.height
-                                    ),
-                                    "normal".to_string(),
-                                    ExtendLayout::LeftToRight,
-                                );
-                                apply_extend_mode(monitors, &default_config)
-                            } else {
-                                Ok(())
-                            }
-                        }
-                        DisplayMode::MainScreenOnly => {
-                            apply_single_screen_mode(monitors, true, &self.config_manager)
-                        }
-                        DisplayMode::SecondScreenOnly => {
-                            apply_single_screen_mode(monitors, false, &self.config_manager)
-                        }
-                    };
-
-                    if let Err(e) = result {
-                        eprintln!("Error applying display mode: {e}");
-                    }
+                    let monitors = monitors.clone();
+                    let retry = Message::SetMode(mode.clone());
+                    return self.apply_with_confirmation(retry, None, move |backend, config_manager| {
+                        apply_display_mode(backend, &monitors, &mode, config_manager)
+                    });
                 }
-                process::exit(0);
+                Task::none()
             }
             Message::OpenExtendSettings => {
                 if let State::Loaded { monitors, .. } = &self.state {
@@ -140,56 +173,65 @@ impl Application for DisplaySwitcher {
 
                     let monitor_names: Vec<String> =
                         monitors.iter().map(|m| m.name.clone()).collect();
-                    let settings = if let Some(saved_config) = self
+                    let (profile_id, extend_config) = if let Some((id, saved_config)) = self
                         .config_manager
-                        .get_extend_configuration_for_monitors(&monitor_names)
+                        .find_profile_for_monitors(monitors)
+                        .filter(|(_, config)| !config.has_disabled_monitors())
                     {
-                        eprintln!(
-                            "Loading saved configuration: Primary={}, Secondary={}",
-                            saved_config.primary_monitor, saved_config.secondary_monitor
-                        );
-
-                        let primary_monitor = &saved_config.primary_monitor;
-                        let secondary_monitor = &saved_config.secondary_monitor;
-
-                        let mut primary_available = vec!["auto".to_string()];
-                        primary_available.extend(get_monitor_available_modes(primary_monitor));
-                        let secondary_available = get_monitor_available_modes(secondary_monitor);
-
-                        MonitorSettingsData {
-                            primary_monitor: saved_config.primary_monitor.clone(),
-                            primary_resolution: saved_config.primary_resolution.clone(),
-                            primary_rotation: saved_config.primary_rotation.clone(),
-                            secondary_resolution: saved_config.secondary_resolution.clone(),
-                            secondary_rotation: saved_config.secondary_rotation.clone(),
-                            layout: saved_config.layout.clone(),
-                            primary_available_resolutions: primary_available,
-                            secondary_available_resolutions: secondary_available,
-                            available_monitors: monitors.iter().map(|m| m.name.clone()).collect(),
-                        }
+                        eprintln!("Loading saved configuration: {}", saved_config.name);
+                        (Some(id.to_string()), saved_config)
                     } else {
+                        let default_config =
+                            default_extend_configuration(monitors, &self.config_manager);
                         eprintln!(
                             "No saved configuration found, using defaults with {} as primary",
-                            monitors[0].name
+                            default_config.primary_monitor
                         );
-                        let mut primary_available = vec!["auto".to_string()];
-                        primary_available.extend(get_monitor_available_modes(&monitors[0].name));
-                        let secondary_available = get_monitor_available_modes(&monitors[1].name);
-
-                        MonitorSettingsData {
-                            primary_monitor: monitors[0].name.clone(),
-                            primary_resolution: "auto".to_string(),
-                            primary_rotation: "normal".to_string(),
-                            secondary_resolution: secondary_available
-                                .first()
-                                .cloned()
-                                .unwrap_or_else(|| "1920x1080".to_string()),
-                            secondary_rotation: "normal".to_string(),
-                            layout: ExtendLayout::LeftToRight,
-                            primary_available_resolutions: primary_available,
-                            secondary_available_resolutions: secondary_available,
-                            available_monitors: monitors.iter().map(|m| m.name.clone()).collect(),
-                        }
+                        (None, default_config)
+                    };
+
+                    let settings = MonitorSettingsData {
+                        profile_id,
+                        profile_name: extend_config.name.clone(),
+                        primary_monitor: extend_config.primary_monitor.clone(),
+                        monitors: extend_config
+                            .monitors
+                            .iter()
+                            .map(|monitor_config| {
+                                let available_modes =
+                                    self.backend.available_modes(&monitor_config.name);
+                                let mut available_resolutions = vec!["auto".to_string()];
+                                for mode in &available_modes {
+                                    if !available_resolutions.contains(&mode.resolution()) {
+                                        available_resolutions.push(mode.resolution());
+                                    }
+                                }
+
+                                MonitorSettingsEntry {
+                                    name: monitor_config.name.clone(),
+                                    resolution: monitor_config.resolution.clone(),
+                                    refresh_rate: monitor_config
+                                        .refresh_rate
+                                        .map(|rate| format!("{rate:.2}"))
+                                        .unwrap_or_else(|| "auto".to_string()),
+                                    scale: format_scale(monitor_config.scale),
+                                    rotation: monitor_config.rotation.clone(),
+                                    available_resolutions,
+                                    available_modes,
+                                    current_size: monitors
+                                        .iter()
+                                        .find(|m| m.name == monitor_config.name)
+                                        .map(|m| (m.width as i32, m.height as i32))
+                                        .unwrap_or_default(),
+                                    position: monitor_config.position.unwrap_or_default(),
+                                    mirror: monitor_config.mirror.clone(),
+                                }
+                            })
+                            .collect(),
+                        layout: extend_config.layout.clone(),
+                        alignment: extend_config.alignment,
+                        alignment_offset: extend_config.alignment_offset.to_string(),
+                        available_monitors: monitor_names,
                     };
 
                     self.state = State::MonitorSettings {
@@ -200,47 +242,69 @@ impl Application for DisplaySwitcher {
                 }
                 Task::none()
             }
-            Message::UpdatePrimaryResolution(resolution) => {
-                if let State::MonitorSettings {
-                    monitors: _,
-                    settings,
-                    ..
-                } = &mut self.state
+            Message::UpdateMonitorResolution(index, resolution) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && let Some(entry) = settings.monitors.get_mut(index)
                 {
-                    settings.primary_resolution = resolution;
+                    entry.resolution = resolution;
+                    // Keep the refresh rate only if the new resolution supports it
+                    if !entry.available_refresh_rates().contains(&entry.refresh_rate) {
+                        entry.refresh_rate = "auto".to_string();
+                    }
+                    if !entry.available_scales().contains(&entry.scale) {
+                        entry.scale = "1".to_string();
+                    }
                 }
                 Task::none()
             }
-            Message::UpdatePrimaryRotation(rotation) => {
-                if let State::MonitorSettings {
-                    monitors: _,
-                    settings,
-                    ..
-                } = &mut self.state
+            Message::UpdateMonitorRefreshRate(index, refresh_rate) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && let Some(entry) = settings.monitors.get_mut(index)
                 {
-                    settings.primary_rotation = rotation;
+                    entry.refresh_rate = refresh_rate;
                 }
                 Task::none()
             }
-            Message::UpdateSecondaryResolution(resolution) => {
-                if let State::MonitorSettings {
-                    monitors: _,
-                    settings,
-                    ..
-                } = &mut self.state
+            Message::UpdateMonitorScale(index, scale) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && let Some(entry) = settings.monitors.get_mut(index)
                 {
-                    settings.secondary_resolution = resolution;
+                    entry.scale = scale;
                 }
                 Task::none()
             }
-            Message::UpdateSecondaryRotation(rotation) => {
-                if let State::MonitorSettings {
-                    monitors: _,
-                    settings,
-                    ..
-                } = &mut self.state
+            Message::UpdateMonitorRotation(index, rotation) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && let Some(entry) = settings.monitors.get_mut(index)
+                {
+                    entry.rotation = rotation;
+                }
+                Task::none()
+            }
+            Message::MoveMonitorUp(index) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && index > 0
+                    && index < settings.monitors.len()
+                {
+                    settings.monitors.swap(index - 1, index);
+                }
+                Task::none()
+            }
+            Message::MoveMonitorDown(index) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && index + 1 < settings.monitors.len()
+                {
+                    settings.monitors.swap(index, index + 1);
+                }
+                Task::none()
+            }
+            Message::MoveMonitorTo(index, x, y) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && index < settings.monitors.len()
                 {
-                    settings.secondary_rotation = rotation;
+                    // Dragging turns a preset into a custom layout
+                    switch_to_custom_layout(settings);
+                    settings.monitors[index].position = (x, y);
                 }
                 Task::none()
             }
@@ -251,68 +315,217 @@ impl Application for DisplaySwitcher {
                     ..
                 } = &mut self.state
                 {
-                    settings.layout = layout;
+                    if layout == ExtendLayout::Custom {
+                        switch_to_custom_layout(settings);
+                    } else {
+                        settings.layout = layout;
+                    }
+                }
+                Task::none()
+            }
+            Message::UpdateAlignment(alignment) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state {
+                    settings.alignment = alignment;
+                }
+                Task::none()
+            }
+            Message::UpdateAlignmentOffset(offset) => {
+                // Only accept input that is on its way to being a number
+                if let State::MonitorSettings { settings, .. } = &mut self.state
+                    && (offset.is_empty() || offset == "-" || offset.parse::<i32>().is_ok())
+                {
+                    settings.alignment_offset = offset;
                 }
                 Task::none()
             }
             Message::UpdatePrimaryMonitor(monitor_name) => {
-                if let State::MonitorSettings { monitors, settings, .. } = &mut self.state {
-                    settings.primary_monitor = monitor_name.clone();
-
-                    let primary_modes = get_monitor_available_modes(&monitor_name);
-                    let mut primary_available = vec!["auto".to_string()];
-                    primary_available.extend(primary_modes);
-                    settings.primary_available_resolutions = primary_available;
-
-                    if let Some(secondary_monitor) =
-                        monitors.iter().find(|m| m.name != monitor_name)
+                if let State::MonitorSettings { settings, .. } = &mut self.state {
+                    // The primary monitor always starts the layout
+                    if let Some(index) = settings
+                        .monitors
+                        .iter()
+                        .position(|m| m.name == monitor_name)
                     {
-                        settings.secondary_available_resolutions =
-                            get_monitor_available_modes(&secondary_monitor.name);
-
-                        settings.primary_resolution = "auto".to_string();
-                        settings.secondary_resolution = settings
-                            .secondary_available_resolutions
-                            .first()
-                            .cloned()
-                            .unwrap_or_else(|| "1920x1080".to_string());
+                        let entry = settings.monitors.remove(index);
+                        settings.monitors.insert(0, entry);
                     }
+                    settings.primary_monitor = monitor_name;
                 }
                 Task::none()
             }
             Message::ApplyExtendSettings => {
                 if let State::MonitorSettings { monitors, settings, .. } = &mut self.state {
-                    let secondary_monitor = monitors
-                        .iter()
-                        .find(|m| m.name != settings.primary_monitor)
-                        .map(|m| m.name.clone())
-                        .unwrap_or_else(|| "Unknown".to_string());
-
-                    let extend_config = ConfigManager::create_config_from_settings(
+                    let mut extend_config = ConfigManager::create_config_from_settings(
                         settings.primary_monitor.clone(),
-                        secondary_monitor,
-                        settings.primary_resolution.clone(),
-                        settings.primary_rotation.clone(),
-                        settings.secondary_resolution.clone(),
-                        settings.secondary_rotation.clone(),
+                        settings
+                            .monitors
+                            .iter()
+                            .map(|entry| MonitorConfiguration {
+                                name: entry.name.clone(),
+                                description: monitors
+                                    .iter()
+                                    .find(|m| m.name == entry.name)
+                                    .map(|m| m.description.clone())
+                                    .unwrap_or_default(),
+                                resolution: entry.resolution.clone(),
+                                refresh_rate: entry.refresh_rate.parse().ok(),
+                                scale: entry.scale.parse().unwrap_or(1.0),
+                                rotation: entry.rotation.clone(),
+                                position: (settings.layout == ExtendLayout::Custom)
+                                    .then_some(entry.position),
+                                mirror: entry.mirror.clone(),
+                                disabled: false,
+                            })
+                            .collect(),
                         settings.layout.clone(),
+                        settings.alignment,
+                        settings.alignment_offset.parse().unwrap_or(0),
                     );
+                    if !settings.profile_name.trim().is_empty() {
+                        extend_config.name = settings.profile_name.trim().to_string();
+                    }
 
-                    if let Err(e) = self
-                        .config_manager
-                        .save_extend_configuration(extend_config.clone())
-                    {
-                        eprintln!("Failed to save configuration: {e}");
+                    let pending_profile = PendingProfile {
+                        id: settings.profile_id.clone(),
+                        config: extend_config.clone(),
+                    };
+                    let monitors = monitors.clone();
+                    return self.apply_with_confirmation(
+                        Message::ApplyExtendSettings,
+                        Some(pending_profile),
+                        move |backend, _| apply_extend_mode(backend, &monitors, &extend_config),
+                    );
+                }
+                Task::none()
+            }
+            Message::UpdateProfileName(name) => {
+                if let State::MonitorSettings { settings, .. } = &mut self.state {
+                    settings.profile_name = name;
+                }
+                Task::none()
+            }
+            Message::OpenProfiles => {
+                if let State::Loaded { monitors, .. } = &self.state {
+                    self.state = State::Profiles {
+                        monitors: monitors.clone(),
+                        selected_index: 0,
+                        renaming: None,
+                    };
+                }
+                Task::none()
+            }
+            Message::SaveCurrentLayout => {
+                let monitors = match self.backend.monitors() {
+                    Ok(monitors) => monitors,
+                    Err(e) => {
+                        eprintln!("Failed to query monitors: {e}");
+                        return Task::none();
                     }
+                };
 
-                    if let Err(e) = apply_extend_mode(monitors, &extend_config) {
-                        eprintln!("Error applying extend mode settings: {e}");
+                let config = capture_current_layout(&*self.backend, &monitors);
+                let name = config.name.clone();
+                match self.config_manager.save_extend_configuration(None, config) {
+                    // Start renaming right away so the layout gets a proper name
+                    Ok(profile_id) => {
+                        if let State::Profiles { renaming, .. } = &mut self.state {
+                            *renaming = Some((profile_id, name));
+                        }
                     }
+                    Err(e) => eprintln!("Failed to save current layout: {e}"),
                 }
-                process::exit(0);
+                Task::none()
+            }
+            Message::IdentifyMonitors => {
+                identify::dismiss_overlays(&mut self.identify_overlays);
+                match self.backend.monitors() {
+                    Ok(monitors) => self.identify_overlays = identify::spawn_overlays(&monitors),
+                    Err(e) => eprintln!("Failed to query monitors: {e}"),
+                }
+                Task::none()
+            }
+            // Any key dismisses the identify labels before doing anything else
+            Message::IcedEvent(Event::Keyboard(keyboard::Event::KeyPressed { .. }))
+                if identify::dismiss_overlays(&mut self.identify_overlays) =>
+            {
+                Task::none()
+            }
+            Message::ApplyProfile(profile_id) => {
+                if let State::Profiles { monitors, .. } = &self.state
+                    && let Some(config) = self
+                        .config_manager
+                        .get_profile(&profile_id)
+                        .and_then(|profile| profile.resolve_for_monitors(monitors))
+                {
+                    let retry = Message::ApplyProfile(profile_id.clone());
+                    let pending_profile = PendingProfile {
+                        id: Some(profile_id),
+                        config: config.clone(),
+                    };
+                    let monitors = monitors.clone();
+                    return self.apply_with_confirmation(retry, Some(pending_profile), move |backend, _| {
+                        apply_extend_mode(backend, &monitors, &config)
+                    });
+                }
+                Task::none()
+            }
+            Message::StartRenameProfile(profile_id) => {
+                if let State::Profiles { renaming, .. } = &mut self.state
+                    && let Some(config) = self.config_manager.get_profile(&profile_id)
+                {
+                    *renaming = Some((profile_id, config.name.clone()));
+                }
+                Task::none()
+            }
+            Message::UpdateRenameDraft(name) => {
+                if let State::Profiles {
+                    renaming: Some((_, draft)),
+                    ..
+                } = &mut self.state
+                {
+                    *draft = name;
+                }
+                Task::none()
+            }
+            Message::SubmitRenameProfile => {
+                if let State::Profiles { renaming, .. } = &mut self.state
+                    && let Some((profile_id, draft)) = renaming.take()
+                    && !draft.trim().is_empty()
+                    && let Err(e) = self
+                        .config_manager
+                        .rename_profile(&profile_id, draft.trim().to_string())
+                {
+                    eprintln!("Failed to rename profile: {e}");
+                }
+                Task::none()
+            }
+            Message::DuplicateProfile(profile_id) => {
+                if let Err(e) = self.config_manager.duplicate_profile(&profile_id) {
+                    eprintln!("Failed to duplicate profile: {e}");
+                }
+                Task::none()
+            }
+            Message::DeleteProfile(profile_id) => {
+                if let Err(e) = self.config_manager.delete_profile(&profile_id) {
+                    eprintln!("Failed to delete profile: {e}");
+                }
+                if let State::Profiles { selected_index, .. } = &mut self.state {
+                    *selected_index = 0;
+                }
+                Task::none()
+            }
+            Message::SetDefaultProfile(profile_id) => {
+                if let State::Profiles { monitors, .. } = &self.state
+                    && let Err(e) = self.config_manager.set_default_profile(&profile_id, monitors)
+                {
+                    eprintln!("Failed to set default profile: {e}");
+                }
+                Task::none()
             }
             Message::BackToMain => {
-                if let State::MonitorSettings { monitors, .. } = &self.state {
+                if let State::MonitorSettings { monitors, .. } | State::Profiles { monitors, .. } =
+                    &self.state
+                {
                     self.state = State::Loaded {
                         monitors: monitors.clone(),
                         selected_index: 0,
@@ -324,104 +537,193 @@ impl Application for DisplaySwitcher {
                 process::exit(0);
             }
             Message::ResetToDefaults => {
-                if let Err(e) = reset_to_defaults() {
-                    eprintln!("Error resetting to defaults: {e}");
+                self.apply_with_confirmation(Message::ResetToDefaults, None, |backend, _| {
+                    reset_to_defaults(backend)
+                })
+            }
+            Message::ChangesApplied(result) => {
+                let State::Applying {
+                    previous_monitors,
+                    previous_mirrors,
+                    pending_profile,
+                    retry,
+                    previous,
+                    output_removed,
+                } = std::mem::replace(&mut self.state, State::Loading)
+                else {
+                    return Task::none();
+                };
+
+                if let Err(e) = result {
+                    eprintln!("Error applying display mode: {e}");
+                    self.state = *previous;
+                    self.show_failure(format!("Failed to apply display mode: {e}"), *retry);
+                    return Task::none();
+                }
+
+                self.state = State::ConfirmChanges {
+                    previous_monitors,
+                    previous_mirrors,
+                    pending_profile,
+                    remaining_seconds: REVERT_TIMEOUT_SECS,
+                };
+                if output_removed {
+                    return self.reopen_on_another_output();
+                }
+                Task::none()
+            }
+            Message::KeepChanges => {
+                if let Err(e) = self.backend.persist() {
+                    eprintln!("Failed to persist layout: {e}");
+                    self.show_failure(
+                        format!("Failed to save the layout for the next start: {e}"),
+                        Message::KeepChanges,
+                    );
+                    return Task::none();
+                }
+                if let State::ConfirmChanges {
+                    pending_profile: Some(pending_profile),
+                    ..
+                } = &self.state
+                    && let Err(e) = self.config_manager.save_extend_configuration(
+                        pending_profile.id.as_deref(),
+                        pending_profile.config.clone(),
+                    )
+                {
+                    eprintln!("Failed to save configuration: {e}");
+                    self.show_failure(
+                        format!("Failed to save configuration: {e}"),
+                        Message::KeepChanges,
+                    );
+                    return Task::none();
                 }
                 process::exit(0);
             }
+            Message::RevertChanges => {
+                if let State::ConfirmChanges {
+                    previous_monitors,
+                    previous_mirrors,
+                    ..
+                } = &self.state
+                {
+                    if let Err(e) =
+                        restore_monitor_state(&*self.backend, previous_monitors, previous_mirrors)
+                    {
+                        eprintln!("Error restoring previous monitor state: {e}");
+                        self.show_failure(
+                            format!("Failed to restore the previous monitor setup: {e}"),
+                            Message::RevertChanges,
+                        );
+                        return Task::none();
+                    }
+                    // A reset already cleared the persisted layout, so write the
+                    // restored one back
+                    if let Err(e) = self.backend.persist() {
+                        eprintln!("Failed to persist restored layout: {e}");
+                    }
+                    self.state = State::Loading;
+                    return self.load_monitors();
+                }
+                Task::none()
+            }
+            Message::RevertCountdownTick => {
+                if let State::ConfirmChanges {
+                    remaining_seconds, ..
+                } = &mut self.state
+                {
+                    *remaining_seconds = remaining_seconds.saturating_sub(1);
+                    if *remaining_seconds == 0 {
+                        return self.update(Message::RevertChanges);
+                    }
+                }
+                Task::none()
+            }
+            Message::RetryFailedAction => {
+                if let State::Error { failed_action, .. } = &mut self.state {
+                    return match failed_action.take() {
+                        Some(action) => {
+                            self.state = action.previous;
+                            self.update(action.retry)
+                        }
+                        None => {
+                            self.state = State::Loading;
+                            self.load_monitors()
+                        }
+                    };
+                }
+                Task::none()
+            }
+            Message::DismissError => {
+                if let State::Error { failed_action, .. } = &mut self.state {
+                    match failed_action.take() {
+                        Some(action) => self.state = action.previous,
+                        // Nothing to go back to, the switcher never got going
+                        None => process::exit(1),
+                    }
+                }
+                Task::none()
+            }
             Message::IcedEvent(Event::Keyboard(keyboard::Event::KeyPressed {
                 key: keyboard::Key::Named(keyboard::key::Named::Escape),
                 ..
             })) => {
-                process::exit(0);
+                match &mut self.state {
+                    // Nothing to revert to until the change is done
+                    State::Applying { .. } => Task::none(),
+                    State::ConfirmChanges { .. } => self.update(Message::RevertChanges),
+                    State::Error { .. } => self.update(Message::DismissError),
+                    State::Profiles {
+                        renaming: renaming @ Some(_),
+                        ..
+                    } => {
+                        *renaming = None;
+                        Task::none()
+                    }
+                    _ => process::exit(0),
+                }
             }
             Message::IcedEvent(Event::Keyboard(keyboard::Event::KeyPressed {
                 key: keyboard::Key::Character(ref c),
                 ..
             })) => {
+                if let State::Applying { .. } = self.state {
+                    return Task::none();
+                }
+                if let State::ConfirmChanges { .. } = self.state {
+                    return match c.as_str() {
+                        "y" => self.update(Message::KeepChanges),
+                        "n" => self.update(Message::RevertChanges),
+                        _ => Task::none(),
+                    };
+                }
+                if let State::Error { .. } = self.state {
+                    return match c.as_str() {
+                        "r" => self.update(Message::RetryFailedAction),
+                        "q" => process::exit(1),
+                        _ => Task::none(),
+                    };
+                }
+
                 match c.as_str() {
-                    "1" => {
-                        // PC screen only
-                        if let State::Loaded { monitors, .. } = &self.state {
-                            let result = apply_single_screen_mode(monitors, true, &self.config_manager);
-                            if let Err(e) = result {
-                                eprintln!("Error applying display mode: {e}");
-                            }
-                        }
-                        process::exit(0);
-                    }
-                    "2" => {
-                        // Duplicate displays
-                        if let State::Loaded { monitors, .. } = &self.state {
-                            let result = apply_mirror_mode(monitors, &self.config_manager);
-                            if let Err(e) = result {
-                                eprintln!("Error applying display mode: {e}");
-                            }
-                        }
-                        process::exit(0);
-                    }
-                    "3" => {
-                        // Extend displays
-                        if let State::Loaded { monitors, .. } = &self.state {
-                            let monitor_names: Vec<String> =
-                                monitors.iter().map(|m| m.name.clone()).collect();
-                            let result = if let Some(saved_config) = self
-                                .config_manager
-                                .get_extend_configuration_for_monitors(&monitor_names)
-                            {
-                                apply_extend_mode(monitors, saved_config)
-                            } else if monitors.len() >= 2 {
-                                let primary_monitor = crate::display::determine_primary_monitor(
-                                    monitors,
-                                    &self.config_manager,
-                                );
-                                let secondary_monitor = monitors
-                                    .iter()
-                                    .find(|m| m.name != primary_monitor.name)
-                                    .unwrap();
-
-                                let default_config = ConfigManager::create_config_from_settings(
-                                    primary_monitor.name.clone(),
-                                    secondary_monitor.name.clone(),
-                                    format!("{}x{}", primary_monitor.width, primary_monitor.height),
-                                    "normal".to_string(),
-                                    format!(
-                                        "{}x{}",
-                                        secondary_monitor.width, secondary_monitor.height
-                                    ),
-                                    "normal".to_string(),
-                                    ExtendLayout::LeftToRight,
-                                );
-                                apply_extend_mode(monitors, &default_config)
-                            } else {
-                                Ok(())
-                            };
-                            if let Err(e) = result {
-                                eprintln!("Error applying display mode: {e}");
-                            }
-                        }
-                        process::exit(0);
-                    }
-                    "4" => {
-                        // Second screen only
-                        if let State::Loaded { monitors, .. } = &self.state {
-                            let result = apply_single_screen_mode(monitors, false, &self.config_manager);
-                            if let Err(e) = result {
-                                eprintln!("Error applying display mode: {e}");
-                            }
-                        }
-                        process::exit(0);
-                    }
+                    "1" => self.update(Message::SetMode(DisplayMode::MainScreenOnly)),
+                    "2" => self.update(Message::SetMode(DisplayMode::Mirror)),
+                    "3" => self.update(Message::SetMode(DisplayMode::Extend)),
+                    "4" => self.update(Message::SetMode(DisplayMode::SecondScreenOnly)),
                     "s" => {
                         // Settings for extend mode
                         self.update(Message::OpenExtendSettings)
                     }
+                    "p" => {
+                        // Profile manager
+                        self.update(Message::OpenProfiles)
+                    }
+                    "i" => {
+                        // Label each output with its name
+                        self.update(Message::IdentifyMonitors)
+                    }
                     "r" => {
                         // Reset to defaults
-                        if let Err(e) = reset_to_defaults() {
-                            eprintln!("Error resetting to defaults: {e}");
-                        }
-                        process::exit(0);
+                        self.update(Message::ResetToDefaults)
                     }
                     "q" => {
                         // Quit
@@ -452,6 +754,9 @@ impl Application for DisplaySwitcher {
                     State::MonitorSettings { selected_field, .. } => {
                         *selected_field = selected_field.saturating_sub(1);
                     }
+                    State::Profiles { selected_index, .. } => {
+                        *selected_index = selected_index.saturating_sub(1);
+                    }
                     _ => {}
                 }
                 Task::none()
@@ -459,7 +764,19 @@ impl Application for DisplaySwitcher {
             Message::NavigateDown => {
                 match &mut self.state {
                     State::Loaded { selected_index, .. } => {
-                        *selected_index = (*selected_index + 1).min(5); // 4 options + 2 buttons
+                        *selected_index = (*selected_index + 1).min(6); // 4 options + 3 buttons
+                    }
+                    State::Profiles {
+                        monitors,
+                        selected_index,
+                        ..
+                    } => {
+                        // Profiles followed by the back button
+                        let last_index = self
+                            .config_manager
+                            .get_profiles_for_monitors(monitors)
+                            .len();
+                        *selected_index = (*selected_index + 1).min(last_index);
                     }
                     State::MonitorSettings { selected_field, .. } => {
                         *selected_field = (*selected_field + 1).min(6); // Number of settings fields
@@ -488,6 +805,7 @@ impl Application for DisplaySwitcher {
                             3 => self.update(Message::SetMode(DisplayMode::SecondScreenOnly)),
                             4 => self.update(Message::Cancel),
                             5 => self.update(Message::ResetToDefaults),
+                            6 => self.update(Message::OpenProfiles),
                             _ => Task::none(),
                         }
                     }
@@ -500,9 +818,41 @@ impl Application for DisplaySwitcher {
                             _ => Task::none(),
                         }
                     }
+                    State::Profiles {
+                        monitors,
+                        selected_index,
+                        ..
+                    } => {
+                        let selected_profile = self
+                            .config_manager
+                            .get_profiles_for_monitors(monitors)
+                            .get(*selected_index)
+                            .map(|(id, _)| id.to_string());
+
+                        match selected_profile {
+                            Some(profile_id) => self.update(Message::ApplyProfile(profile_id)),
+                            None => self.update(Message::BackToMain),
+                        }
+                    }
+                    State::ConfirmChanges { .. } => self.update(Message::KeepChanges),
+                    State::Error { .. } => self.update(Message::RetryFailedAction),
                     _ => Task::none(),
                 }
             }
+            Message::MonitorEvent(MonitorEvent::Removed(name))
+                if self.output.as_ref() == Some(&name) =>
+            {
+                // Reopen once the change is done so it can still be confirmed
+                if let State::Applying { output_removed, .. } = &mut self.state {
+                    *output_removed = true;
+                    return Task::none();
+                }
+                self.reopen_on_another_output()
+            }
+            Message::MonitorEvent(_) if matches!(self.state, State::Loaded { .. }) => {
+                let backend = self.backend.clone();
+                Task::perform(async move { backend.monitors() }, Message::MonitorsRefreshed)
+            }
             _ => Task::none(),
         }
     }
@@ -516,17 +866,29 @@ impl Application for DisplaySwitcher {
                 .align_y(alignment::Vertical::Center)
                 .style(ui::container_style())
                 .into(),
-            State::Error { message } => container(text(format!("Error: {message}")).size(16))
+            State::Applying { .. } => container(text("Applying display changes...").size(20))
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .align_x(alignment::Horizontal::Center)
                 .align_y(alignment::Vertical::Center)
                 .style(ui::container_style())
                 .into(),
+            State::Error {
+                message,
+                failed_action,
+            } => self.create_error_view(message, failed_action.is_some()),
 
-            State::MonitorSettings { monitors, settings, .. } => {
-                self.create_monitor_settings_view(monitors, settings)
-            }
+            State::MonitorSettings { settings, .. } => self.create_monitor_settings_view(settings),
+
+            State::Profiles {
+                monitors,
+                selected_index,
+                renaming,
+            } => self.create_profiles_view(monitors, *selected_index, renaming.as_ref()),
+
+            State::ConfirmChanges {
+                remaining_seconds, ..
+            } => self.create_confirm_changes_view(*remaining_seconds),
 
             State::Loaded { monitors, selected_index } => {
                 let title = text("Choose display mode")
@@ -588,7 +950,18 @@ impl Application for DisplaySwitcher {
                 .style(ui::action_button_style_with_selection(*selected_index == 5, ui::ActionButtonType::Reset))
                 .on_press(Message::ResetToDefaults);
 
-                let keyboard_help = text("Keyboard: 1-4 (quick select) • ↑↓ (navigate) • Enter/Space (select) • S (settings) • R (reset) • Q/Esc (quit)")
+                let profiles_text = if *selected_index == 6 { "▶ Profiles (P)" } else { "Profiles (P)" };
+
+                let profiles_button = button(
+                    container(text(profiles_text).size(16).style(ui::cancel_text_style()))
+                        .padding(Padding::from([12, 24]))
+                        .align_x(alignment::Horizontal::Center),
+                )
+                .width(Length::Fill)
+                .style(ui::action_button_style_with_selection(*selected_index == 6, ui::ActionButtonType::Profiles))
+                .on_press(Message::OpenProfiles);
+
+                let keyboard_help = text("Keyboard: 1-4 (quick select) • ↑↓ (navigate) • Enter/Space (select) • S (settings) • P (profiles) • I (identify) • R (reset) • Q/Esc (quit)")
                     .size(11)
                     .style(ui::subtitle_text_style());
 
@@ -602,7 +975,7 @@ impl Application for DisplaySwitcher {
                         extend_card,
                         second_screen_card,
                         Space::with_height(16),
-                        row![cancel_button, reset_button].spacing(12),
+                        row![cancel_button, reset_button, profiles_button].spacing(12),
                         Space::with_height(8),
                         keyboard_help
                     ]
@@ -626,7 +999,19 @@ impl Application for DisplaySwitcher {
     }
 
     fn subscription(&self) -> iced::Subscription<Message> {
-        event::listen().map(Message::IcedEvent)
+        let events = iced::Subscription::batch([
+            event::listen().map(Message::IcedEvent),
+            iced::Subscription::run(monitor_events),
+        ]);
+
+        if let State::ConfirmChanges { .. } = self.state {
+            iced::Subscription::batch([
+                events,
+                time::every(Duration::from_secs(1)).map(|_| Message::RevertCountdownTick),
+            ])
+        } else {
+            events
+        }
     }
 
     fn style(&self, theme: &Self::Theme) -> iced_layershell::Appearance {
@@ -638,95 +1023,422 @@ impl Application for DisplaySwitcher {
 }
 
 impl DisplaySwitcher {
-    fn new() -> Result<Self, String> {
+    fn new(backend: Arc<dyn DisplayBackend>) -> Result<Self, String> {
         let config_manager = ConfigManager::new()
             .map_err(|e| format!("Failed to initialize config manager: {e}"))?;
 
         Ok(Self {
             state: State::Loading,
             config_manager,
+            backend,
+            identify_overlays: Vec::new(),
+            output: None,
         })
     }
 
-    fn create_monitor_settings_view<'a>(
-        &self,
-        monitors: &[Monitor],
-        settings: &'a MonitorSettingsData,
-    ) -> Element<'a, Message> {
-        let title = text("Monitor Settings - Extend Mode")
-            .size(24)
-            .style(ui::title_text_style());
+    /// Layer surfaces cannot move between outputs, so start a new switcher,
+    /// which opens on the now focused monitor, and exit. A pending
+    /// confirmation is handed over so the change can still be kept or
+    /// reverted.
+    fn reopen_on_another_output(&self) -> Task<Message> {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                eprintln!("Failed to locate executable to reopen switcher: {e}");
+                return Task::none();
+            }
+        };
 
-        let primary_monitor_name = &settings.primary_monitor;
-        let secondary_monitor_name = monitors
-            .iter()
-            .find(|m| m.name != settings.primary_monitor)
-            .map(|m| m.name.as_str())
-            .unwrap_or("Secondary");
+        let mut command = process::Command::new(exe);
+        command.env_remove(RESUME_ENV);
+        if let State::ConfirmChanges {
+            previous_monitors,
+            previous_mirrors,
+            pending_profile,
+            remaining_seconds,
+        } = &self.state
+        {
+            let resume = ConfirmResume {
+                previous_monitors: previous_monitors.clone(),
+                previous_mirrors: previous_mirrors.clone(),
+                pending_profile: pending_profile.clone(),
+                remaining_seconds: *remaining_seconds,
+            };
+            match serde_json::to_string(&resume) {
+                Ok(json) => {
+                    command.env(RESUME_ENV, json);
+                }
+                Err(e) => eprintln!("Failed to hand over pending confirmation: {e}"),
+            }
+        }
 
-        let primary_section = column![
-            text(format!("Primary Monitor ({primary_monitor_name})"))
-                .size(16)
-                .style(ui::card_title_text_style()),
-            row![
-                column![
-                    text("Resolution:").size(12),
-                    pick_list(
-                        settings.primary_available_resolutions.as_slice(),
-                        settings
-                            .primary_available_resolutions
-                            .iter()
-                            .find(|&r| r == &settings.primary_resolution),
-                        |res| Message::UpdatePrimaryResolution(res.clone())
-                    )
-                    .width(120)
-                ]
-                .spacing(4),
-                column![
-                    text("Rotation:").size(12),
-                    pick_list(
-                        vec!["normal", "left", "right", "inverted"],
-                        Some(settings.primary_rotation.as_str()),
-                        |rot| Message::UpdatePrimaryRotation(rot.to_string())
-                    )
-                    .width(100)
+        match command.spawn() {
+            Ok(_) => process::exit(0),
+            Err(e) => {
+                eprintln!("Failed to reopen switcher: {e}");
+                Task::none()
+            }
+        }
+    }
+
+    /// Applies a display change and asks the user to keep it, reverting to the
+    /// monitor state from before the change if nobody confirms in time.
+    fn apply_with_confirmation(
+        &mut self,
+        retry: Message,
+        pending_profile: Option<PendingProfile>,
+        apply: impl FnOnce(&dyn DisplayBackend, &ConfigManager) -> Result<(), String>
+        + Send
+        + 'static,
+    ) -> Task<Message> {
+        let previous_monitors = match self.backend.monitors() {
+            Ok(monitors) => monitors,
+            Err(_) => match &self.state {
+                State::Loaded { monitors, .. } | State::MonitorSettings { monitors, .. } => {
+                    monitors.clone()
+                }
+                _ => Vec::new(),
+            },
+        };
+        let previous_mirrors = mirror_sources(&*self.backend, &previous_monitors);
+
+        let previous = std::mem::replace(&mut self.state, State::Loading);
+        self.state = State::Applying {
+            previous_monitors,
+            previous_mirrors,
+            pending_profile,
+            retry: Box::new(retry),
+            previous: Box::new(previous),
+            output_removed: false,
+        };
+
+        // Hyprland can take a while to bring outputs up, keep the UI responsive
+        let backend = self.backend.clone();
+        let config_manager = self.config_manager.clone();
+        Task::perform(
+            async move { apply(&*backend, &config_manager) },
+            Message::ChangesApplied,
+        )
+    }
+
+    fn create_profiles_view<'a>(
+        &'a self,
+        monitors: &[Monitor],
+        selected_index: usize,
+        renaming: Option<&'a (String, String)>,
+    ) -> Element<'a, Message> {
+        let title = text("Profiles").size(24).style(ui::title_text_style());
+
+        let monitor_names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
+        let profiles = self.config_manager.get_profiles_for_monitors(monitors);
+
+        let subtitle = text(format!(
+            "{} profile{} for {} • Enter to apply, Esc to quit",
+            profiles.len(),
+            if profiles.len() == 1 { "" } else { "s" },
+            monitor_names.join(" + ")
+        ))
+        .size(14)
+        .style(ui::subtitle_text_style());
+
+        let profile_rows = profiles.iter().enumerate().map(|(index, (id, config))| {
+            let id = id.to_string();
+
+            let name: Element<'a, Message> = match renaming {
+                Some((renaming_id, draft)) if *renaming_id == id => {
+                    text_input("Profile name", draft)
+                        .on_input(Message::UpdateRenameDraft)
+                        .on_submit(Message::SubmitRenameProfile)
+                        .size(14)
+                        .width(Length::Fill)
+                        .into()
+                }
+                _ => {
+                    let marker = if index == selected_index { "▶ " } else { "" };
+                    let default_badge = if self.config_manager.is_default_profile(&id) {
+                        " ★ default"
+                    } else {
+                        ""
+                    };
+                    text(format!("{marker}{}{default_badge}", config.name))
+                        .size(16)
+                        .style(ui::card_title_text_style())
+                        .width(Length::Fill)
+                        .into()
+                }
+            };
+
+            let action = |label: &'a str, message: Message| {
+                button(text(label).size(12))
+                    .padding(Padding::from([4, 8]))
+                    .style(ui::settings_button_style())
+                    .on_press(message)
+            };
+
+            column![
+                name,
+                row![
+                    action("Apply", Message::ApplyProfile(id.clone())),
+                    action("Rename", Message::StartRenameProfile(id.clone())),
+                    action("Duplicate", Message::DuplicateProfile(id.clone())),
+                    action("Set default", Message::SetDefaultProfile(id.clone())),
+                    button(text("Delete").size(12))
+                        .padding(Padding::from([4, 8]))
+                        .style(ui::cancel_button_style())
+                        .on_press(Message::DeleteProfile(id)),
                 ]
-                .spacing(4)
+                .spacing(6)
+            ]
+            .spacing(6)
+            .into()
+        });
+
+        let back_text = if selected_index == profiles.len() { "▶ Back" } else { "Back" };
+        let back_button = button(
+            container(text(back_text).size(14))
+                .padding(Padding::from([8, 16]))
+                .align_x(alignment::Horizontal::Center),
+        )
+        .style(ui::cancel_button_style())
+        .on_press(Message::BackToMain);
+
+        let save_current_button = button(
+            container(text("Save current layout").size(14))
+                .padding(Padding::from([8, 16]))
+                .align_x(alignment::Horizontal::Center),
+        )
+        .style(ui::card_button_style())
+        .on_press(Message::SaveCurrentLayout);
+
+        container(
+            column![
+                title,
+                subtitle,
+                Space::with_height(16),
+                Column::with_children(profile_rows).spacing(16),
+                Space::with_height(16),
+                row![back_button, save_current_button].spacing(12)
             ]
-            .spacing(16)
+            .spacing(8)
+            .padding(24)
+            .width(Length::Fill)
+            .align_x(alignment::Horizontal::Center),
+        )
+        .width(480)
+        .style(ui::main_container_style())
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .padding(Padding::from(16))
+        .into()
+    }
+
+    /// Shows the error screen for an action that failed in the current
+    /// state. Retry and Back both return to that state first.
+    fn show_failure(&mut self, message: String, retry: Message) {
+        let previous = std::mem::replace(&mut self.state, State::Loading);
+        self.state = State::Error {
+            message,
+            failed_action: Some(Box::new(FailedAction { retry, previous })),
+        };
+    }
+
+    fn create_error_view<'a>(&self, message: &'a str, can_go_back: bool) -> Element<'a, Message> {
+        let title = text("Something went wrong")
+            .size(24)
+            .style(ui::title_text_style());
+
+        let details = text(message).size(14).style(ui::subtitle_text_style());
+
+        let (back_label, hint) = if can_go_back {
+            ("Back", "Enter/R to retry, Esc to go back")
+        } else {
+            ("Close", "Enter/R to retry, Esc to close")
+        };
+
+        let buttons = row![
+            button(
+                container(text(back_label).size(14))
+                    .padding(Padding::from([8, 16]))
+                    .align_x(alignment::Horizontal::Center)
+            )
+            .style(ui::cancel_button_style())
+            .on_press(Message::DismissError),
+            button(
+                container(text("Retry").size(14))
+                    .padding(Padding::from([8, 16]))
+                    .align_x(alignment::Horizontal::Center)
+            )
+            .style(ui::card_button_style())
+            .on_press(Message::RetryFailedAction)
         ]
-        .spacing(8);
+        .spacing(12);
 
-        let secondary_section = column![
-            text(format!("Secondary Monitor ({secondary_monitor_name})"))
-                .size(16)
-                .style(ui::card_title_text_style()),
-            row![
-                column![
-                    text("Resolution:").size(12),
-                    pick_list(
-                        settings.secondary_available_resolutions.as_slice(),
-                        settings
-                            .secondary_available_resolutions
-                            .iter()
-                            .find(|&r| r == &settings.secondary_resolution),
-                        |res| Message::UpdateSecondaryResolution(res.clone())
-                    )
-                    .width(120)
-                ]
-                .spacing(4),
-                column![
-                    text("Rotation:").size(12),
-                    pick_list(
-                        vec!["normal", "left", "right", "inverted"],
-                        Some(settings.secondary_rotation.as_str()),
-                        |rot| Message::UpdateSecondaryRotation(rot.to_string())
-                    )
-                    .width(100)
+        container(
+            column![
+                title,
+                details,
+                text(hint).size(12).style(ui::subtitle_text_style()),
+                Space::with_height(16),
+                buttons
+            ]
+            .spacing(8)
+            .padding(24)
+            .width(Length::Fill)
+            .align_x(alignment::Horizontal::Center),
+        )
+        .width(480)
+        .style(ui::main_container_style())
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .padding(Padding::from(16))
+        .into()
+    }
+
+    fn create_confirm_changes_view(&self, remaining_seconds: u64) -> Element<'_, Message> {
+        let title = text("Keep these settings?")
+            .size(24)
+            .style(ui::title_text_style());
+
+        let subtitle = text(format!(
+            "Reverting in {remaining_seconds}s • Enter/Y to keep, Esc/N to revert"
+        ))
+        .size(14)
+        .style(ui::subtitle_text_style());
+
+        let buttons = row![
+            button(
+                container(text("Revert").size(14))
+                    .padding(Padding::from([8, 16]))
+                    .align_x(alignment::Horizontal::Center)
+            )
+            .style(ui::cancel_button_style())
+            .on_press(Message::RevertChanges),
+            button(
+                container(text("Keep changes").size(14))
+                    .padding(Padding::from([8, 16]))
+                    .align_x(alignment::Horizontal::Center)
+            )
+            .style(ui::card_button_style())
+            .on_press(Message::KeepChanges)
+        ]
+        .spacing(12);
+
+        container(
+            column![title, subtitle, Space::with_height(16), buttons]
+                .spacing(8)
+                .padding(24)
+                .width(Length::Fill)
+                .align_x(alignment::Horizontal::Center),
+        )
+        .width(480)
+        .style(ui::main_container_style())
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .padding(Padding::from(16))
+        .into()
+    }
+
+    fn load_monitors(&self) -> Task<Message> {
+        let backend = self.backend.clone();
+        Task::perform(async move { backend.monitors() }, Message::MonitorsLoaded)
+    }
+
+    fn create_monitor_settings_view<'a>(
+        &self,
+        settings: &'a MonitorSettingsData,
+    ) -> Element<'a, Message> {
+        let title = text("Monitor Settings - Extend Mode")
+            .size(24)
+            .style(ui::title_text_style());
+
+        let monitor_count = settings.monitors.len();
+        let monitor_sections = settings.monitors.iter().enumerate().map(|(index, entry)| {
+            let role = if entry.name == settings.primary_monitor {
+                "Primary"
+            } else {
+                "Monitor"
+            };
+
+            column![
+                row![
+                    text(format!("{}. {role} ({})", index + 1, entry.name))
+                        .size(16)
+                        .style(ui::card_title_text_style())
+                        .width(Length::Fill),
+                    button(text("↑").size(14))
+                        .style(ui::settings_button_style())
+                        .on_press_maybe((index > 0).then_some(Message::MoveMonitorUp(index))),
+                    button(text("↓").size(14))
+                        .style(ui::settings_button_style())
+                        .on_press_maybe(
+                            (index + 1 < monitor_count).then_some(Message::MoveMonitorDown(index))
+                        ),
                 ]
                 .spacing(4)
+                .align_y(alignment::Vertical::Center),
+                row![
+                    column![
+                        text("Resolution:").size(12),
+                        pick_list(
+                            entry.available_resolutions.as_slice(),
+                            entry
+                                .available_resolutions
+                                .iter()
+                                .find(|&r| r == &entry.resolution),
+                            move |res| Message::UpdateMonitorResolution(index, res.clone())
+                        )
+                        .width(120)
+                    ]
+                    .spacing(4),
+                    column![
+                        text("Refresh rate:").size(12),
+                        pick_list(
+                            entry.available_refresh_rates(),
+                            Some(entry.refresh_rate.clone()),
+                            move |rate| Message::UpdateMonitorRefreshRate(index, rate)
+                        )
+                        .width(100)
+                    ]
+                    .spacing(4)
+                ]
+                .spacing(16),
+                row![
+                    column![
+                        text("Scale:").size(12),
+                        pick_list(
+                            entry.available_scales(),
+                            Some(entry.scale.clone()),
+                            move |scale| Message::UpdateMonitorScale(index, scale)
+                        )
+                        .width(120)
+                    ]
+                    .spacing(4),
+                    column![
+                        text("Rotation:").size(12),
+                        pick_list(
+                            ROTATIONS,
+                            Some(entry.rotation.as_str()),
+                            move |rot| Message::UpdateMonitorRotation(index, rot.to_string())
+                        )
+                        .width(150)
+                    ]
+                    .spacing(4)
+                ]
+                .spacing(16)
             ]
-            .spacing(16)
+            .spacing(8)
+            .into()
+        });
+
+        let monitors_section = Column::with_children(monitor_sections).spacing(16);
+
+        let profile_name_section = column![
+            text("Profile Name:")
+                .size(16)
+                .style(ui::card_title_text_style()),
+            text_input("Profile name", &settings.profile_name)
+                .on_input(Message::UpdateProfileName)
+                .width(200)
         ]
         .spacing(8);
 
@@ -746,6 +1458,16 @@ impl DisplaySwitcher {
         ]
         .spacing(8);
 
+        let primary_monitor_section = row![
+            primary_monitor_section,
+            button(text("Identify").size(14))
+                .padding(Padding::from([6, 12]))
+                .style(ui::settings_button_style())
+                .on_press(Message::IdentifyMonitors)
+        ]
+        .spacing(12)
+        .align_y(alignment::Vertical::Bottom);
+
         let layout_section = column![
             text("Layout:").size(16).style(ui::card_title_text_style()),
             pick_list(
@@ -753,7 +1475,8 @@ impl DisplaySwitcher {
                     ExtendLayout::LeftToRight,
                     ExtendLayout::RightToLeft,
                     ExtendLayout::TopToBottom,
-                    ExtendLayout::BottomToTop
+                    ExtendLayout::BottomToTop,
+                    ExtendLayout::Custom
                 ],
                 Some(&settings.layout),
                 Message::UpdateLayout
@@ -762,6 +1485,61 @@ impl DisplaySwitcher {
         ]
         .spacing(8);
 
+        let alignment_hint = match settings.layout {
+            ExtendLayout::LeftToRight | ExtendLayout::RightToLeft => "start = top edges",
+            ExtendLayout::TopToBottom | ExtendLayout::BottomToTop => "start = left edges",
+            ExtendLayout::Custom => "",
+        };
+
+        let layout_editor = LayoutEditor {
+            monitors: settings
+                .monitors
+                .iter()
+                .zip(settings.positions())
+                .map(|(entry, position)| EditorMonitor {
+                    name: entry.name.clone(),
+                    resolution: entry.resolution.clone(),
+                    position,
+                    size: entry.logical_size(),
+                    primary: entry.name == settings.primary_monitor,
+                })
+                .collect(),
+        };
+
+        let layout_editor_section = column![
+            text("Arrangement (drag to move):")
+                .size(16)
+                .style(ui::card_title_text_style()),
+            canvas(layout_editor).width(Length::Fill).height(160)
+        ]
+        .spacing(8);
+
+        // Alignment only applies to the preset layouts
+        let alignment_section = (settings.layout != ExtendLayout::Custom).then(|| column![
+            text(format!("Alignment ({alignment_hint}):"))
+                .size(16)
+                .style(ui::card_title_text_style()),
+            row![
+                pick_list(
+                    vec![
+                        ExtendAlignment::Start,
+                        ExtendAlignment::Center,
+                        ExtendAlignment::End
+                    ],
+                    Some(settings.alignment),
+                    Message::UpdateAlignment
+                )
+                .width(120),
+                text_input("Offset (px)", &settings.alignment_offset)
+                    .on_input(Message::UpdateAlignmentOffset)
+                    .width(120),
+                text("px offset").size(12)
+            ]
+            .spacing(8)
+            .align_y(alignment::Vertical::Center)
+        ]
+        .spacing(8));
+
         let buttons = row![
             button(
                 container(text("Back").size(14))
@@ -791,16 +1569,19 @@ impl DisplaySwitcher {
             column![
                 title,
                 Space::with_height(16),
-                primary_monitor_section,
+                profile_name_section,
                 Space::with_height(16),
-                primary_section,
+                primary_monitor_section,
                 Space::with_height(16),
-                secondary_section,
+                monitors_section,
                 Space::with_height(16),
                 layout_section,
-                Space::with_height(20),
-                buttons
+                Space::with_height(16),
+                layout_editor_section,
             ]
+            .push_maybe(alignment_section)
+            .push(Space::with_height(20))
+            .push(buttons)
             .spacing(8)
             .padding(24)
             .width(Length::Fill)
@@ -814,7 +1595,282 @@ impl DisplaySwitcher {
         .into()
     }
 
-    pub fn run(settings: Settings<()>) -> Result<(), iced_layershell::Error> {
+    pub fn run(settings: Settings<LaunchOptions>) -> Result<(), iced_layershell::Error> {
         <DisplaySwitcher as Application>::run(settings)
     }
 }
+
+/// Switches the settings to a custom layout, keeping the monitors where the
+/// current layout places them
+fn switch_to_custom_layout(settings: &mut MonitorSettingsData) {
+    if settings.layout == ExtendLayout::Custom {
+        return;
+    }
+
+    let positions = settings.positions();
+    for (entry, position) in settings.monitors.iter_mut().zip(positions) {
+        entry.position = position;
+    }
+    settings.layout = ExtendLayout::Custom;
+}
+
+/// Monitor hotplug events from Hyprland's event socket. The socket is read on
+/// its own thread since reads block.
+fn monitor_events() -> impl Stream<Item = Message> {
+    stream::channel(16, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+
+        std::thread::spawn(move || {
+            let mut events = match ipc::EventStream::connect() {
+                Ok(events) => events,
+                Err(e) => {
+                    eprintln!("Not watching for monitor changes: {e}");
+                    return;
+                }
+            };
+
+            while let Some(line) = events.next_event() {
+                let event = if line.starts_with("monitoradded>>") {
+                    MonitorEvent::Added
+                } else if let Some(name) = line.strip_prefix("monitorremoved>>") {
+                    MonitorEvent::Removed(name.to_string())
+                } else {
+                    continue;
+                };
+
+                if sender.unbounded_send(Message::MonitorEvent(event)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(message) = receiver.next().await {
+            if output.send(message).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{FakeBackend, PersistingBackend};
+    use crate::display::{apply_mirror_mode, apply_single_output_mode};
+
+    fn loaded_switcher() -> (DisplaySwitcher, Arc<FakeBackend>) {
+        let backend = Arc::new(FakeBackend::with_sample_monitors());
+        (switcher_for(backend.clone()), backend)
+    }
+
+    fn switcher_for(backend: Arc<dyn DisplayBackend>) -> DisplaySwitcher {
+        DisplaySwitcher {
+            state: State::Loaded {
+                monitors: backend.monitors().unwrap(),
+                selected_index: 0,
+            },
+            config_manager: ConfigManager::default(),
+            backend,
+            identify_overlays: Vec::new(),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn changes_are_confirmed_once_applied() {
+        let (mut app, backend) = loaded_switcher();
+        let previous = backend.monitors().unwrap();
+
+        let _ = app.update(Message::SetMode(DisplayMode::MainScreenOnly));
+        assert!(matches!(app.state, State::Applying { .. }));
+
+        let _ = app.update(Message::ChangesApplied(Ok(())));
+        match &app.state {
+            State::ConfirmChanges {
+                previous_monitors,
+                remaining_seconds,
+                ..
+            } => {
+                assert_eq!(previous_monitors, &previous);
+                assert_eq!(*remaining_seconds, REVERT_TIMEOUT_SECS);
+            }
+            state => panic!("expected confirmation, got {state:?}"),
+        }
+    }
+
+    #[test]
+    fn keys_are_ignored_while_applying() {
+        let (mut app, _) = loaded_switcher();
+        let _ = app.update(Message::SetMode(DisplayMode::Mirror));
+
+        let _ = app.update(Message::IcedEvent(Event::Keyboard(
+            keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                modified_key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                physical_key: keyboard::key::Physical::Unidentified(
+                    keyboard::key::NativeCode::Unidentified,
+                ),
+                location: keyboard::Location::Standard,
+                modifiers: keyboard::Modifiers::empty(),
+                text: None,
+            },
+        )));
+
+        assert!(matches!(app.state, State::Applying { .. }));
+    }
+
+    #[test]
+    fn mode_changes_outside_the_mode_list_are_ignored() {
+        let (mut app, _) = loaded_switcher();
+        let _ = app.update(Message::SetMode(DisplayMode::Mirror));
+
+        let _ = app.update(Message::SetMode(DisplayMode::Extend));
+        let _ = app.update(Message::ApplyExtendSettings);
+
+        assert!(matches!(app.state, State::Applying { .. }));
+    }
+
+    #[test]
+    fn failed_apply_can_be_retried() {
+        let (mut app, _) = loaded_switcher();
+        let _ = app.update(Message::SetMode(DisplayMode::MainScreenOnly));
+
+        let _ = app.update(Message::ChangesApplied(Err("boom".to_string())));
+        match &app.state {
+            State::Error {
+                message,
+                failed_action: Some(action),
+            } => {
+                assert!(message.contains("boom"));
+                assert!(matches!(
+                    action.retry,
+                    Message::SetMode(DisplayMode::MainScreenOnly)
+                ));
+                assert!(matches!(action.previous, State::Loaded { .. }));
+            }
+            state => panic!("expected a retryable error, got {state:?}"),
+        }
+
+        let _ = app.update(Message::RetryFailedAction);
+        assert!(matches!(app.state, State::Applying { .. }));
+    }
+
+    #[test]
+    fn dismissing_a_failed_apply_goes_back() {
+        let (mut app, _) = loaded_switcher();
+        let _ = app.update(Message::SetMode(DisplayMode::MainScreenOnly));
+        let _ = app.update(Message::ChangesApplied(Err("boom".to_string())));
+
+        let _ = app.update(Message::DismissError);
+
+        assert!(matches!(app.state, State::Loaded { .. }));
+    }
+
+    #[test]
+    fn revert_restores_the_previous_monitors() {
+        let (mut app, backend) = loaded_switcher();
+        let previous = backend.monitors().unwrap();
+        let _ = app.update(Message::SetMode(DisplayMode::SecondScreenOnly));
+        apply_single_output_mode(&*backend, &previous, "DP-3").unwrap();
+        let _ = app.update(Message::ChangesApplied(Ok(())));
+
+        let _ = app.update(Message::RevertChanges);
+
+        assert!(matches!(app.state, State::Loading));
+        assert_eq!(backend.monitors().unwrap(), previous);
+    }
+
+    #[test]
+    fn revert_restores_mirroring() {
+        let (mut app, backend) = loaded_switcher();
+        apply_mirror_mode(&*backend, &backend.monitors().unwrap(), &ConfigManager::default())
+            .unwrap();
+        app.state = State::Loaded {
+            monitors: backend.monitors().unwrap(),
+            selected_index: 0,
+        };
+
+        let _ = app.update(Message::SetMode(DisplayMode::Extend));
+        apply_single_output_mode(&*backend, &backend.monitors().unwrap(), "eDP-1").unwrap();
+        let _ = app.update(Message::ChangesApplied(Ok(())));
+        assert_eq!(backend.mirror_source("DP-3"), None);
+
+        let _ = app.update(Message::RevertChanges);
+
+        assert_eq!(backend.mirror_source("DP-3").as_deref(), Some("eDP-1"));
+        assert!(!backend.monitors().unwrap().iter().any(|m| m.disabled));
+    }
+
+    #[test]
+    fn reverting_a_reset_persists_the_restored_layout() {
+        let dir = std::env::temp_dir().join(format!("display-switcher-app-{}", process::id()));
+        let path = dir.join("monitors.conf");
+        let backend = Arc::new(PersistingBackend::new(
+            FakeBackend::with_sample_monitors(),
+            path.clone(),
+            true,
+        ));
+        let mut app = switcher_for(backend.clone());
+
+        let _ = app.update(Message::ResetToDefaults);
+        reset_to_defaults(&*backend).unwrap();
+        let _ = app.update(Message::ChangesApplied(Ok(())));
+        let _ = app.update(Message::RevertChanges);
+
+        let persisted = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(persisted.matches("monitor=").count(), 2);
+    }
+
+    #[test]
+    fn countdown_reverts_when_it_runs_out() {
+        let (mut app, backend) = loaded_switcher();
+        let previous = backend.monitors().unwrap();
+        apply_single_output_mode(&*backend, &previous, "DP-3").unwrap();
+        app.state = State::ConfirmChanges {
+            previous_monitors: previous.clone(),
+            previous_mirrors: Vec::new(),
+            pending_profile: None,
+            remaining_seconds: 2,
+        };
+
+        let _ = app.update(Message::RevertCountdownTick);
+        assert!(matches!(
+            app.state,
+            State::ConfirmChanges {
+                remaining_seconds: 1,
+                ..
+            }
+        ));
+
+        let _ = app.update(Message::RevertCountdownTick);
+        assert!(matches!(app.state, State::Loading));
+        assert_eq!(backend.monitors().unwrap(), previous);
+    }
+
+    #[test]
+    fn failed_revert_can_be_retried() {
+        let (mut app, backend) = loaded_switcher();
+        let mut previous = backend.monitors().unwrap();
+        previous[1].name = "HDMI-A-1".to_string();
+        app.state = State::ConfirmChanges {
+            previous_monitors: previous,
+            previous_mirrors: Vec::new(),
+            pending_profile: None,
+            remaining_seconds: REVERT_TIMEOUT_SECS,
+        };
+
+        let _ = app.update(Message::RevertChanges);
+
+        match &app.state {
+            State::Error {
+                failed_action: Some(action),
+                ..
+            } => {
+                assert!(matches!(action.retry, Message::RevertChanges));
+                assert!(matches!(action.previous, State::ConfirmChanges { .. }));
+            }
+            state => panic!("expected a retryable error, got {state:?}"),
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use crate::config::ConfigManager;
+use crate::ipc;
+use crate::monitor_rule::{MonitorMode, MonitorRule};
+use crate::monitors_conf;
+use hyprland::data::{Monitor, Monitors, Transforms, WorkspaceBasic};
+use hyprland::shared::HyprData;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Environment variable that switches to the in-memory backend, so the
+/// switcher can be run and exercised without a Hyprland session.
+pub const FAKE_BACKEND_ENV: &str = "DISPLAY_SWITCHER_FAKE_BACKEND";
+
+/// Everything the switcher needs from the compositor.
+pub trait DisplayBackend: Send + Sync {
+    /// Connected monitors, including disabled ones with `disabled` set
+    fn monitors(&self) -> Result<Vec<Monitor>, String>;
+
+    /// Modes the monitor supports
+    fn available_modes(&self, monitor_name: &str) -> Vec<AvailableMode>;
+
+    /// Monitor whose content the monitor mirrors, if any
+    fn mirror_source(&self, monitor_name: &str) -> Option<String>;
+
+    /// Applies the rules together, so the compositor never sees a partial
+    /// configuration, and returns once the resulting monitor changes have
+    /// taken effect
+    fn apply_monitor_rules(&self, rules: &[MonitorRule]) -> Result<(), String>;
+
+    /// Reloads the compositor configuration, dropping runtime monitor rules
+    fn reload(&self) -> Result<(), String>;
+
+    /// Saves the live layout so it survives a restart, for backends that hold
+    /// off on that until a change is confirmed
+    fn persist(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A mode a monitor supports
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailableMode {
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: f32,
+}
+
+impl AvailableMode {
+    /// Resolution in `1920x1080` form
+    pub fn resolution(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+}
+
+pub fn default_backend() -> Arc<dyn DisplayBackend> {
+    create_backend(false)
+}
+
+/// Backend for the switcher window, which only persists a layout once it is
+/// kept with `DisplayBackend::persist`
+pub fn confirming_backend() -> Arc<dyn DisplayBackend> {
+    create_backend(true)
+}
+
+fn create_backend(deferred: bool) -> Arc<dyn DisplayBackend> {
+    if std::env::var_os(FAKE_BACKEND_ENV).is_some() {
+        eprintln!("Using fake display backend");
+        Arc::new(FakeBackend::with_sample_monitors())
+    } else {
+        let monitors_conf_path = ConfigManager::new()
+            .ok()
+            .and_then(|config_manager| config_manager.get_monitors_conf_path());
+        match monitors_conf_path {
+            Some(path) => Arc::new(PersistingBackend::new(HyprlandBackend, path, deferred)),
+            None => Arc::new(HyprlandBackend),
+        }
+    }
+}
+
+/// How long to wait for outputs to appear or disappear after applying rules
+const MONITOR_EVENT_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub struct HyprlandBackend;
+
+impl DisplayBackend for HyprlandBackend {
+    fn monitors(&self) -> Result<Vec<Monitor>, String> {
+        Monitors::get()
+            .map(|monitors| monitors.into_iter().collect::<Vec<Monitor>>())
+            .map_err(|e| e.to_string())
+    }
+
+    fn available_modes(&self, monitor_name: &str) -> Vec<AvailableMode> {
+        let monitor_modes = ipc::monitors_with_modes().map(|monitors| {
+            monitors
+                .into_iter()
+                .find(|m| m.name == monitor_name)
+                .map(|m| m.available_modes)
+                .unwrap_or_default()
+        });
+
+        match monitor_modes {
+            Ok(available_modes) => {
+                let modes = parse_monitor_modes(&available_modes);
+                eprintln!("Available modes for {monitor_name}: {modes:?}");
+                modes
+            }
+            Err(e) => {
+                eprintln!("Failed to get modes for {monitor_name}, using fallback: {e}");
+                // Fallback to common resolutions if the request fails
+                fallback_modes(&[
+                    (1920, 1080),
+                    (2560, 1440),
+                    (3840, 2160),
+                    (1680, 1050),
+                    (1366, 768),
+                    (1440, 900),
+                ])
+            }
+        }
+    }
+
+    fn mirror_source(&self, monitor_name: &str) -> Option<String> {
+        let monitors = ipc::monitors_with_modes()
+            .map_err(|e| eprintln!("Failed to get mirror source of {monitor_name}: {e}"))
+            .ok()?;
+        monitors
+            .into_iter()
+            .find(|m| m.name == monitor_name)
+            .map(|m| m.mirror_of)
+            .filter(|mirror_of| !mirror_of.is_empty() && mirror_of != "none")
+    }
+
+    fn apply_monitor_rules(&self, rules: &[MonitorRule]) -> Result<(), String> {
+        let active: Vec<String> = self
+            .monitors()?
+            .into_iter()
+            .filter(|m| !m.disabled)
+            .map(|m| m.name)
+            .collect();
+
+        // Outputs that get enabled or disabled announce it on the event socket
+        let expected_events: Vec<String> = rules
+            .iter()
+            .filter_map(|rule| match (rule.disabled, active.contains(&rule.name)) {
+                (true, true) => Some(format!("monitorremoved>>{}", rule.name)),
+                (false, false) => Some(format!("monitoradded>>{}", rule.name)),
+                _ => None,
+            })
+            .collect();
+
+        let events = ipc::EventStream::connect();
+        let commands: Vec<String> = rules
+            .iter()
+            .map(|rule| format!("keyword monitor {rule}"))
+            .collect();
+        ipc::batch(&commands)?;
+
+        match events {
+            Ok(events) => {
+                let missing = events.wait_for(expected_events, MONITOR_EVENT_TIMEOUT);
+                if !missing.is_empty() {
+                    eprintln!("Timed out waiting for monitor events: {missing:?}");
+                }
+            }
+            Err(e) => eprintln!("Not waiting for monitor events: {e}"),
+        }
+
+        Ok(())
+    }
+
+    fn reload(&self) -> Result<(), String> {
+        ipc::request_ok("reload")
+    }
+}
+
+/// Writes every successfully applied set of rules to a Hyprland config file,
+/// see `monitors_conf`, so the layout outlives the runtime rules
+pub struct PersistingBackend<B> {
+    inner: B,
+    path: PathBuf,
+    /// Write the layout on `persist` instead of whenever rules are applied
+    deferred: bool,
+    /// Set by a reload, which already cleared the persisted layout
+    reloaded: AtomicBool,
+}
+
+impl<B> PersistingBackend<B> {
+    pub fn new(inner: B, path: PathBuf, deferred: bool) -> Self {
+        Self {
+            inner,
+            path,
+            deferred,
+            reloaded: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<B: DisplayBackend> DisplayBackend for PersistingBackend<B> {
+    fn monitors(&self) -> Result<Vec<Monitor>, String> {
+        self.inner.monitors()
+    }
+
+    fn available_modes(&self, monitor_name: &str) -> Vec<AvailableMode> {
+        self.inner.available_modes(monitor_name)
+    }
+
+    fn mirror_source(&self, monitor_name: &str) -> Option<String> {
+        self.inner.mirror_source(monitor_name)
+    }
+
+    fn apply_monitor_rules(&self, rules: &[MonitorRule]) -> Result<(), String> {
+        self.inner.apply_monitor_rules(rules)?;
+        self.reloaded.store(false, Ordering::Relaxed);
+        if self.deferred {
+            return Ok(());
+        }
+        monitors_conf::write(&self.path, rules)
+    }
+
+    /// Clears the persisted layout first, otherwise the reload would apply
+    /// it again. This can't wait for `persist`.
+    fn reload(&self) -> Result<(), String> {
+        monitors_conf::write(&self.path, &[])?;
+        self.inner.reload()?;
+        self.reloaded.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Writes the monitors as they are now, which also covers changes made
+    /// by a switcher that was reopened on another output and layouts put
+    /// back after a reset was reverted
+    fn persist(&self) -> Result<(), String> {
+        if !self.deferred || self.reloaded.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let rules: Vec<MonitorRule> = self
+            .inner
+            .monitors()?
+            .iter()
+            .map(|monitor| MonitorRule {
+                mirror: self.inner.mirror_source(&monitor.name),
+                ..MonitorRule::from_monitor(monitor)
+            })
+            .collect();
+        monitors_conf::write(&self.path, &rules)
+    }
+}
+
+/// In-memory backend that logs applied rules instead of talking to
+/// Hyprland. Like Hyprland, `monitors()` keeps reporting disabled monitors
+/// with `disabled` set; a reload puts every monitor back as it started.
+pub struct FakeBackend {
+    state: Mutex<FakeState>,
+}
+
+struct FakeState {
+    /// Monitors as configured before any rules were applied
+    initial: Vec<Monitor>,
+    monitors: Vec<Monitor>,
+    /// Mirroring monitor and the monitor it mirrors
+    mirrors: Vec<(String, String)>,
+}
+
+impl FakeBackend {
+    pub fn new(monitors: Vec<Monitor>) -> Self {
+        Self {
+            state: Mutex::new(FakeState {
+                initial: monitors.clone(),
+                monitors,
+                mirrors: Vec::new(),
+            }),
+        }
+    }
+
+    /// A laptop panel with an external monitor to the right
+    pub fn with_sample_monitors() -> Self {
+        Self::new(vec![
+            fake_monitor(0, "eDP-1", "Laptop panel", 1920, 1200, 0, true),
+            fake_monitor(1, "DP-3", "External monitor", 2560, 1440, 1920, false),
+        ])
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        // A panic while holding the lock leaves the data usable for a fake
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl DisplayBackend for FakeBackend {
+    fn monitors(&self) -> Result<Vec<Monitor>, String> {
+        Ok(self.lock().monitors.clone())
+    }
+
+    fn available_modes(&self, monitor_name: &str) -> Vec<AvailableMode> {
+        self.lock()
+            .monitors
+            .iter()
+            .find(|m| m.name == monitor_name)
+            .map(|m| {
+                let (width, height) = (m.width as i32, m.height as i32);
+                vec![
+                    AvailableMode {
+                        width,
+                        height,
+                        refresh_rate: 144.0,
+                    },
+                    AvailableMode {
+                        width,
+                        height,
+                        refresh_rate: m.refresh_rate,
+                    },
+                    AvailableMode {
+                        width: 1920,
+                        height: 1080,
+                        refresh_rate: 60.0,
+                    },
+                ]
+            })
+            .unwrap_or_default()
+    }
+
+    fn mirror_source(&self, monitor_name: &str) -> Option<String> {
+        self.lock()
+            .mirrors
+            .iter()
+            .find(|(mirroring, _)| mirroring == monitor_name)
+            .map(|(_, source)| source.clone())
+    }
+
+    fn apply_monitor_rules(&self, rules: &[MonitorRule]) -> Result<(), String> {
+        let mut state = self.lock();
+        // Validate everything first so a bad rule leaves the state untouched
+        if let Some(rule) = rules
+            .iter()
+            .find(|rule| !state.monitors.iter().any(|m| m.name == rule.name))
+        {
+            return Err(format!("Unknown monitor in rule: {rule}"));
+        }
+
+        for rule in rules {
+            if let Some(monitor) = state.monitors.iter_mut().find(|m| m.name == rule.name) {
+                monitor.disabled = rule.disabled;
+                if !rule.disabled {
+                    if let MonitorMode::Resolution {
+                        width,
+                        height,
+                        refresh_rate,
+                    } = rule.mode
+                    {
+                        monitor.width = width as u16;
+                        monitor.height = height as u16;
+                        monitor.refresh_rate = refresh_rate.unwrap_or(monitor.refresh_rate);
+                    }
+                    (monitor.x, monitor.y) = rule.position;
+                    monitor.scale = rule.scale;
+                    monitor.transform = rule.transform;
+                }
+            }
+            state
+                .mirrors
+                .retain(|(mirroring, _)| mirroring != &rule.name);
+            if let Some(source) = &rule.mirror {
+                state.mirrors.push((rule.name.clone(), source.clone()));
+            }
+
+            eprintln!("Fake backend applied rule: {rule}");
+        }
+        Ok(())
+    }
+
+    fn reload(&self) -> Result<(), String> {
+        let mut state = self.lock();
+        state.monitors = state.initial.clone();
+        state.mirrors.clear();
+        Ok(())
+    }
+}
+
+fn fake_monitor(
+    id: i128,
+    name: &str,
+    description: &str,
+    width: u16,
+    height: u16,
+    x: i32,
+    focused: bool,
+) -> Monitor {
+    Monitor {
+        id,
+        name: name.to_string(),
+        description: description.to_string(),
+        width,
+        height,
+        refresh_rate: 60.0,
+        x,
+        y: 0,
+        active_workspace: WorkspaceBasic {
+            id: id as i32 + 1,
+            name: (id + 1).to_string(),
+        },
+        special_workspace: WorkspaceBasic {
+            id: 0,
+            name: String::new(),
+        },
+        reserved: (0, 0, 0, 0),
+        scale: 1.0,
+        transform: Transforms::Normal,
+        focused,
+        dpms_status: true,
+        vrr: false,
+        disabled: false,
+    }
+}
+
+/// Unique modes from Hyprland's `availableModes`, e.g. `1920x1080@60.00Hz`
+fn parse_monitor_modes(available_modes: &[String]) -> Vec<AvailableMode> {
+    let mut modes = Vec::new();
+    for mode_str in available_modes {
+        if let Some(mode) = parse_available_mode(mode_str)
+            && !modes.contains(&mode)
+        {
+            modes.push(mode);
+        }
+    }
+
+    // If no modes found, provide fallback
+    if modes.is_empty() {
+        modes = fallback_modes(&[(1920, 1080), (2560, 1440), (3840, 2160)]);
+    }
+
+    modes
+}
+
+fn parse_available_mode(mode: &str) -> Option<AvailableMode> {
+    let (resolution, refresh_rate) = mode.trim().split_once('@')?;
+    let (width, height) = resolution.split_once('x')?;
+
+    Some(AvailableMode {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh_rate: refresh_rate
+            .trim_end_matches("Hz")
+            .trim_end_matches("hz")
+            .parse()
+            .ok()?,
+    })
+}
+
+fn fallback_modes(resolutions: &[(i32, i32)]) -> Vec<AvailableMode> {
+    resolutions
+        .iter()
+        .map(|&(width, height)| AvailableMode {
+            width,
+            height,
+            refresh_rate: 60.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn temp_monitors_conf(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "display-switcher-backend-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("monitors.conf")
+    }
+
+    fn monitor_lines(path: &Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| line.starts_with("monitor="))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn applied_rules_are_written_right_away() {
+        let path = temp_monitors_conf("direct");
+        let backend =
+            PersistingBackend::new(FakeBackend::with_sample_monitors(), path.clone(), false);
+
+        backend
+            .apply_monitor_rules(&[MonitorRule::disable("DP-3")])
+            .unwrap();
+
+        assert_eq!(monitor_lines(&path), ["monitor=DP-3,disable"]);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn deferred_rules_are_written_on_persist() {
+        let path = temp_monitors_conf("deferred");
+        let backend =
+            PersistingBackend::new(FakeBackend::with_sample_monitors(), path.clone(), true);
+
+        backend
+            .apply_monitor_rules(&[MonitorRule::disable("DP-3")])
+            .unwrap();
+        assert!(!path.exists());
+
+        backend.persist().unwrap();
+        assert_eq!(
+            monitor_lines(&path),
+            [
+                "monitor=eDP-1,1920x1200@60.00,0x0,1",
+                "monitor=DP-3,disable"
+            ]
+        );
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn persisting_after_a_reload_keeps_the_layout_cleared() {
+        let path = temp_monitors_conf("reload");
+        let backend =
+            PersistingBackend::new(FakeBackend::with_sample_monitors(), path.clone(), true);
+
+        backend.reload().unwrap();
+        backend.persist().unwrap();
+
+        assert!(path.exists());
+        assert!(monitor_lines(&path).is_empty());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::fsutil::write_atomic;
+use crate::migrate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Tag of the backups taken before every save, which are rotated
+pub const ROTATED_TAG: &str = "auto";
+
+/// A copy of the configuration file in the `backups` directory, named
+/// `config-<timestamp>-<tag>.json`
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// Local time the backup was taken, as `YYYYmmdd-HHMMSSmmm`, followed by
+    /// `.<n>` for further backups taken within the same millisecond
+    pub timestamp: String,
+    /// Why it was taken: `auto`, `restore`, `invalid` or the format version
+    /// it was migrated from, e.g. `v0`
+    pub tag: String,
+}
+
+impl Backup {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let stem = path
+            .file_name()?
+            .to_str()?
+            .strip_prefix("config-")?
+            .strip_suffix(".json")?;
+        let (date, rest) = stem.split_once('-')?;
+        let (time, tag) = rest.split_once('-')?;
+        let (time, counter) = match time.split_once('.') {
+            Some((time, counter)) => (time, Some(counter)),
+            None => (time, None),
+        };
+        if !is_digits(date, 8)
+            || !is_digits(time, 9)
+            || counter.is_some_and(|counter| counter.parse::<u32>().is_err())
+            || tag.is_empty()
+        {
+            return None;
+        }
+
+        Some(Self {
+            timestamp: stem[..stem.len() - tag.len() - 1].to_string(),
+            tag: tag.to_string(),
+            path,
+        })
+    }
+}
+
+fn is_digits(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub fn backup_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("backups")
+}
+
+/// Copies the configuration file into the backup directory
+pub fn create(config_path: &Path, tag: &str) -> Result<PathBuf, String> {
+    let backup_path = new_backup_path(config_path, tag)?;
+    fs::copy(config_path, &backup_path)
+        .map_err(|e| format!("Failed to back up to {}: {e}", backup_path.display()))?;
+    Ok(backup_path)
+}
+
+/// Moves the configuration file into the backup directory, so the next
+/// start begins without one
+pub fn move_aside(config_path: &Path, tag: &str) -> Result<PathBuf, String> {
+    let backup_path = new_backup_path(config_path, tag)?;
+    fs::rename(config_path, &backup_path)
+        .map_err(|e| format!("Failed to move to {}: {e}", backup_path.display()))?;
+    Ok(backup_path)
+}
+
+fn new_backup_path(config_path: &Path, tag: &str) -> Result<PathBuf, String> {
+    let backup_dir = backup_dir(config_path);
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", backup_dir.display()))?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+    let mut backup_path = backup_dir.join(format!("config-{timestamp}-{tag}.json"));
+    // Never overwrite an earlier backup taken within the same millisecond
+    let mut counter = 1;
+    while backup_path.exists() {
+        counter += 1;
+        backup_path = backup_dir.join(format!("config-{timestamp}.{counter}-{tag}.json"));
+    }
+    Ok(backup_path)
+}
+
+/// All backups, newest first
+pub fn list(config_path: &Path) -> Result<Vec<Backup>, String> {
+    let backup_dir = backup_dir(config_path);
+    let entries = match fs::read_dir(&backup_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", backup_dir.display())),
+    };
+
+    let mut backups: Vec<Backup> = entries
+        .filter_map(|entry| Backup::from_path(entry.ok()?.path()))
+        .collect();
+    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.path.cmp(&a.path)));
+    Ok(backups)
+}
+
+/// Deletes all but the newest `keep` rotated backups. Backups taken for
+/// another reason, such as a migration, are kept.
+pub fn prune(config_path: &Path, keep: usize) -> Result<(), String> {
+    for backup in list(config_path)?
+        .into_iter()
+        .filter(|backup| backup.tag == ROTATED_TAG)
+        .skip(keep)
+    {
+        fs::remove_file(&backup.path)
+            .map_err(|e| format!("Failed to remove {}: {e}", backup.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Replaces the configuration with a backup after checking that it can be
+/// loaded. The configuration being replaced is backed up first; its backup
+/// is returned.
+pub fn restore(config_path: &Path, backup: &Backup) -> Result<Option<PathBuf>, String> {
+    let content = fs::read_to_string(&backup.path)
+        .map_err(|e| format!("Failed to read {}: {e}", backup.path.display()))?;
+    migrate::parse_config(&content)
+        .map_err(|e| format!("{} is not a valid configuration: {e}", backup.file_name()))?;
+
+    let previous = if config_path.exists() {
+        Some(create(config_path, "restore")?)
+    } else {
+        None
+    };
+
+    write_atomic(config_path, content.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {e}", config_path.display()))?;
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Configuration file in a fresh temporary directory
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "display-switcher-backup-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(
+            &config_path,
+            r#"{"version": 1, "extend_configurations": {}}"#,
+        )
+        .unwrap();
+        config_path
+    }
+
+    fn tags(config_path: &Path) -> Vec<String> {
+        let mut tags: Vec<String> = list(config_path)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.tag)
+            .collect();
+        tags.sort();
+        tags
+    }
+
+    #[test]
+    fn backup_names_round_trip() {
+        let config_path = temp_config("names");
+
+        let first = Backup::from_path(create(&config_path, "v0").unwrap()).unwrap();
+        let second = Backup::from_path(create(&config_path, "v0").unwrap()).unwrap();
+
+        assert_eq!(first.tag, "v0");
+        assert_eq!(second.tag, "v0");
+        assert_ne!(first.path, second.path);
+        assert_eq!(first.timestamp.len(), "20241016-120000123".len());
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[test]
+    fn unrelated_files_are_not_backups() {
+        for name in [
+            "config.json",
+            "config-a-b-auto.json",
+            "config-20241016-120000123.json",
+            "config-20241016-120000123.x-auto.json",
+            "config-20241016-120000123-auto.txt",
+        ] {
+            assert!(Backup::from_path(PathBuf::from(name)).is_none(), "{name}");
+        }
+
+        let backup =
+            Backup::from_path(PathBuf::from("config-20241016-120000123.2-auto.json")).unwrap();
+        assert_eq!(backup.timestamp, "20241016-120000123.2");
+        assert_eq!(backup.tag, "auto");
+    }
+
+    #[test]
+    fn prune_only_removes_old_rotated_backups() {
+        let config_path = temp_config("prune");
+        for tag in ["v0", "invalid", "restore"] {
+            create(&config_path, tag).unwrap();
+        }
+        for _ in 0..4 {
+            create(&config_path, ROTATED_TAG).unwrap();
+        }
+        let newest: Vec<PathBuf> = list(&config_path)
+            .unwrap()
+            .into_iter()
+            .filter(|backup| backup.tag == ROTATED_TAG)
+            .take(2)
+            .map(|backup| backup.path)
+            .collect();
+
+        prune(&config_path, 2).unwrap();
+
+        assert_eq!(
+            tags(&config_path),
+            ["auto", "auto", "invalid", "restore", "v0"]
+        );
+        assert!(newest.iter().all(|path| path.exists()));
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[test]
+    fn restore_replaces_the_configuration_and_backs_it_up() {
+        let config_path = temp_config("restore");
+        let backup = Backup::from_path(create(&config_path, ROTATED_TAG).unwrap()).unwrap();
+        let restored = fs::read_to_string(&backup.path).unwrap();
+        fs::write(
+            &config_path,
+            r#"{"version": 1, "extend_configurations": {}, "last_used_extend_config": "a"}"#,
+        )
+        .unwrap();
+
+        let previous = restore(&config_path, &backup).unwrap().unwrap();
+
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), restored);
+        assert!(
+            fs::read_to_string(previous)
+                .unwrap()
+                .contains("last_used_extend_config")
+        );
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    #[test]
+    fn restore_rejects_invalid_backups() {
+        let config_path = temp_config("invalid");
+        let original = fs::read_to_string(&config_path).unwrap();
+        let backup = Backup::from_path(create(&config_path, ROTATED_TAG).unwrap()).unwrap();
+        fs::write(&backup.path, "{ not json").unwrap();
+
+        assert!(restore(&config_path, &backup).is_err());
+
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+        assert_eq!(tags(&config_path), ["auto"]);
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+}
@@ -0,0 +1,467 @@
+use crate::backend::{DisplayBackend, default_backend};
+use crate::backup;
+use crate::config::{self, ConfigManager};
+use crate::daemon;
+use crate::display::{
+    apply_display_mode, apply_single_output_mode, capture_current_layout, reset_to_defaults,
+};
+use crate::identify;
+use crate::import;
+use crate::notify;
+use crate::state::DisplayMode;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// Exit codes returned by headless commands
+pub const EXIT_APPLY_FAILED: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_MONITORS_UNAVAILABLE: u8 = 3;
+pub const EXIT_CONFIG_FAILED: u8 = 4;
+
+pub const USAGE: &str = "\
+Usage: hyprland-display-switcher [COMMAND]
+
+Without a command the switcher overlay is shown.
+
+Commands:
+  mirror          Duplicate the primary monitor on the other display
+  extend          Extend across displays using the saved configuration
+  primary-only    Use only the primary monitor
+  secondary-only  Use only the secondary monitor
+  only NAME       Use only the named monitor, e.g. `only HDMI-A-1`
+  reset           Reload the Hyprland configuration
+  identify        Show each monitor's name, description and mode on it
+  save-current [NAME]
+                  Save the layout the monitors are in now as a profile
+  import [FILE]   Save the monitor rules from a Hyprland config, and the files
+                  it sources, as a profile (default ~/.config/hypr/hyprland.conf)
+  backups         List backups of the configuration, newest first
+  restore-backup BACKUP
+                  Replace the configuration with a backup, given by its number
+                  in `backups` or its file name
+  daemon          Re-apply saved configurations when monitors are plugged in
+  help            Show this message
+
+Daemon options:
+  --fallback MODE  Mode to apply when no saved configuration matches
+                   (mirror, extend, primary-only or secondary-only)";
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Gui,
+    SetMode(DisplayMode),
+    SingleOutput(String),
+    Reset,
+    Identify,
+    SaveCurrent(Option<String>),
+    Import(Option<PathBuf>),
+    ListBackups,
+    RestoreBackup(String),
+    /// Label overlay for a single output, started by `identify`
+    IdentifyOutput(String),
+    Daemon {
+        fallback: Option<DisplayMode>,
+    },
+    Help,
+}
+
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+
+    let command = match args.next().as_deref() {
+        None => Command::Gui,
+        Some("mirror") => Command::SetMode(DisplayMode::Mirror),
+        Some("extend") => Command::SetMode(DisplayMode::Extend),
+        Some("primary-only") => Command::SetMode(DisplayMode::MainScreenOnly),
+        Some("secondary-only") => Command::SetMode(DisplayMode::SecondScreenOnly),
+        Some("only") => Command::SingleOutput(args.next().ok_or("only requires a monitor name")?),
+        Some("reset") => Command::Reset,
+        Some("identify") => Command::Identify,
+        Some("save-current") => Command::SaveCurrent(args.next()),
+        Some("backups") => Command::ListBackups,
+        Some("restore-backup") => Command::RestoreBackup(
+            args.next()
+                .ok_or("restore-backup requires a backup number or file name")?,
+        ),
+        Some("import") => Command::Import(args.next().map(PathBuf::from)),
+        Some("identify-output") => Command::IdentifyOutput(
+            args.next()
+                .ok_or("identify-output requires a monitor name")?,
+        ),
+        Some("daemon") => {
+            let fallback = match args.next().as_deref() {
+                None => None,
+                Some("--fallback") => Some(
+                    args.next()
+                        .ok_or("--fallback requires a mode")?
+                        .parse::<DisplayMode>()?,
+                ),
+                Some(other) => return Err(format!("Unexpected argument: {other}")),
+            };
+            Command::Daemon { fallback }
+        }
+        Some("help" | "-h" | "--help") => Command::Help,
+        Some(other) => return Err(format!("Unknown command: {other}")),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("Unexpected argument: {extra}"));
+    }
+
+    Ok(command)
+}
+
+/// Reports an argument error from `parse_args` along with the usage
+pub fn usage_error(error: &str) -> ExitCode {
+    eprintln!("{error}\n\n{USAGE}");
+    ExitCode::from(EXIT_USAGE)
+}
+
+pub fn run(command: Command) -> ExitCode {
+    let backend = default_backend();
+
+    match command {
+        // Overlays are launched by main, nothing to do here
+        Command::Gui | Command::IdentifyOutput(_) => ExitCode::SUCCESS,
+        Command::Help => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Command::Reset => match notify_result(
+            &*backend,
+            "Configuration reloaded",
+            reset_to_defaults(&*backend),
+        ) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error resetting to defaults: {e}");
+                ExitCode::from(EXIT_APPLY_FAILED)
+            }
+        },
+        Command::SetMode(mode) => set_mode(&*backend, &mode),
+        Command::Identify => match backend.monitors() {
+            Ok(monitors) => {
+                for mut overlay in identify::spawn_overlays(&monitors) {
+                    let _ = overlay.wait();
+                }
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to query monitors: {e}");
+                ExitCode::from(EXIT_MONITORS_UNAVAILABLE)
+            }
+        },
+        Command::SingleOutput(name) => match backend.monitors() {
+            Ok(monitors) => match notify_result(
+                &*backend,
+                &format!("Only {name}"),
+                apply_single_output_mode(&*backend, &monitors, &name),
+            ) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error applying display mode: {e}");
+                    ExitCode::from(EXIT_APPLY_FAILED)
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to query monitors: {e}");
+                ExitCode::from(EXIT_MONITORS_UNAVAILABLE)
+            }
+        },
+        Command::SaveCurrent(name) => save_current(&*backend, name),
+        Command::Import(path) => import_config(&*backend, path),
+        Command::ListBackups => list_backups(),
+        Command::RestoreBackup(backup) => restore_backup(&backup),
+        Command::Daemon { fallback } => match daemon::run(backend, fallback) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Hotplug daemon stopped: {e}");
+                ExitCode::from(EXIT_MONITORS_UNAVAILABLE)
+            }
+        },
+    }
+}
+
+fn set_mode(backend: &dyn DisplayBackend, mode: &DisplayMode) -> ExitCode {
+    let mut config_manager = match ConfigManager::new() {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            eprintln!("Failed to initialize config manager: {e}");
+            return ExitCode::from(EXIT_CONFIG_FAILED);
+        }
+    };
+
+    let monitors = match backend.monitors() {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        Ok(_) => {
+            eprintln!("No monitors detected");
+            return ExitCode::from(EXIT_MONITORS_UNAVAILABLE);
+        }
+        Err(e) => {
+            eprintln!("Failed to query monitors: {e}");
+            return ExitCode::from(EXIT_MONITORS_UNAVAILABLE);
+        }
+    };
+
+    import::import_on_first_run(&mut config_manager, &monitors);
+    let result = apply_display_mode(backend, &monitors, mode, &config_manager);
+    notify::notify_result(&config_manager, backend, notify::mode_title(mode), &result);
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error applying display mode: {e}");
+            ExitCode::from(EXIT_APPLY_FAILED)
+        }
+    }
+}
+
+fn save_current(backend: &dyn DisplayBackend, name: Option<String>) -> ExitCode {
+    let mut config_manager = match ConfigManager::new() {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            eprintln!("Failed to initialize config manager: {e}");
+            return ExitCode::from(EXIT_CONFIG_FAILED);
+        }
+    };
+
+    let monitors = match backend.monitors() {
+        Ok(monitors) if monitors.iter().any(|m| !m.disabled) => monitors,
+        Ok(_) => {
+            eprintln!("No enabled monitors detected");
+            return ExitCode::from(EXIT_MONITORS_UNAVAILABLE);
+        }
+        Err(e) => {
+            eprintln!("Failed to query monitors: {e}");
+            return ExitCode::from(EXIT_MONITORS_UNAVAILABLE);
+        }
+    };
+
+    let mut config = capture_current_layout(backend, &monitors);
+    if let Some(name) = name {
+        config.name = name;
+    }
+    let name = config.name.clone();
+
+    match config_manager.save_extend_configuration(None, config) {
+        Ok(profile_id) => {
+            println!("Saved profile \"{name}\" ({profile_id})");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error saving current layout: {e}");
+            ExitCode::from(EXIT_CONFIG_FAILED)
+        }
+    }
+}
+
+fn list_backups() -> ExitCode {
+    let backups = match backup::list(&config::config_path()) {
+        Ok(backups) => backups,
+        Err(e) => {
+            eprintln!("Error listing backups: {e}");
+            return ExitCode::from(EXIT_CONFIG_FAILED);
+        }
+    };
+
+    if backups.is_empty() {
+        println!("No backups yet");
+    }
+    for (index, backup) in backups.iter().enumerate() {
+        println!("{:>3}  {}  ({})", index + 1, backup.file_name(), backup.tag);
+    }
+    ExitCode::SUCCESS
+}
+
+fn restore_backup(selector: &str) -> ExitCode {
+    let config_path = config::config_path();
+    let backups = match backup::list(&config_path) {
+        Ok(backups) => backups,
+        Err(e) => {
+            eprintln!("Error listing backups: {e}");
+            return ExitCode::from(EXIT_CONFIG_FAILED);
+        }
+    };
+
+    let selected = match selector.parse::<usize>() {
+        Ok(number) => number.checked_sub(1).and_then(|index| backups.get(index)),
+        Err(_) => backups.iter().find(|backup| backup.file_name() == selector),
+    };
+    let Some(selected) = selected else {
+        eprintln!("No backup {selector}, see `backups`");
+        return ExitCode::from(EXIT_USAGE);
+    };
+
+    match backup::restore(&config_path, selected) {
+        Ok(previous) => {
+            println!("Restored {}", selected.file_name());
+            if let Some(previous) = previous {
+                println!(
+                    "The replaced configuration was saved to {}",
+                    previous.display()
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error restoring backup: {e}");
+            ExitCode::from(EXIT_CONFIG_FAILED)
+        }
+    }
+}
+
+fn import_config(backend: &dyn DisplayBackend, path: Option<PathBuf>) -> ExitCode {
+    let mut config_manager = match ConfigManager::new() {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            eprintln!("Failed to initialize config manager: {e}");
+            return ExitCode::from(EXIT_CONFIG_FAILED);
+        }
+    };
+
+    // The profile covers the connected monitors, so they have to be known
+    let monitors = match backend.monitors() {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            eprintln!("Failed to query monitors: {e}");
+            return ExitCode::from(EXIT_MONITORS_UNAVAILABLE);
+        }
+    };
+    let path = path.unwrap_or_else(import::default_config_path);
+
+    match import::import_profile(&path, &mut config_manager, &monitors) {
+        Ok(profile_id) => {
+            if let Some(profile) = config_manager.get_profile(&profile_id) {
+                println!("Saved profile \"{}\" ({profile_id})", profile.name);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error importing monitor rules: {e}");
+            ExitCode::from(EXIT_CONFIG_FAILED)
+        }
+    }
+}
+
+/// Sends the notification for a mode change and passes its result through. A
+/// configuration that fails to load leaves notifications enabled.
+fn notify_result(
+    backend: &dyn DisplayBackend,
+    title: &str,
+    result: Result<(), String>,
+) -> Result<(), String> {
+    let config_manager = ConfigManager::new().unwrap_or_default();
+    notify::notify_result(&config_manager, backend, title, &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_shows_the_switcher() {
+        assert!(matches!(parse(&[]), Ok(Command::Gui)));
+    }
+
+    #[test]
+    fn mode_commands_parse_to_their_mode() {
+        assert!(matches!(
+            parse(&["secondary-only"]),
+            Ok(Command::SetMode(DisplayMode::SecondScreenOnly))
+        ));
+        assert!(matches!(
+            parse(&["only", "HDMI-A-1"]),
+            Ok(Command::SingleOutput(name)) if name == "HDMI-A-1"
+        ));
+    }
+
+    #[test]
+    fn unknown_command_is_rejected() {
+        assert_eq!(parse(&["mirorr"]).unwrap_err(), "Unknown command: mirorr");
+    }
+
+    #[test]
+    fn extra_arguments_are_rejected() {
+        assert_eq!(
+            parse(&["mirror", "now"]).unwrap_err(),
+            "Unexpected argument: now"
+        );
+        assert_eq!(
+            parse(&["save-current", "Desk", "Home"]).unwrap_err(),
+            "Unexpected argument: Home"
+        );
+    }
+
+    #[test]
+    fn only_requires_a_monitor_name() {
+        assert_eq!(
+            parse(&["only"]).unwrap_err(),
+            "only requires a monitor name"
+        );
+    }
+
+    #[test]
+    fn daemon_fallback_is_parsed() {
+        assert!(matches!(
+            parse(&["daemon"]),
+            Ok(Command::Daemon { fallback: None })
+        ));
+        assert!(matches!(
+            parse(&["daemon", "--fallback", "extend"]),
+            Ok(Command::Daemon {
+                fallback: Some(DisplayMode::Extend)
+            })
+        ));
+    }
+
+    #[test]
+    fn daemon_fallback_requires_a_valid_mode() {
+        assert_eq!(
+            parse(&["daemon", "--fallback"]).unwrap_err(),
+            "--fallback requires a mode"
+        );
+        assert_eq!(
+            parse(&["daemon", "--fallback", "sideways"]).unwrap_err(),
+            "Unknown display mode: sideways"
+        );
+        assert_eq!(
+            parse(&["daemon", "--verbose"]).unwrap_err(),
+            "Unexpected argument: --verbose"
+        );
+        assert_eq!(
+            parse(&["daemon", "--fallback", "mirror", "extra"]).unwrap_err(),
+            "Unexpected argument: extra"
+        );
+    }
+
+    #[test]
+    fn argument_errors_exit_with_the_usage_code() {
+        assert_eq!(
+            usage_error("Unknown command: mirorr"),
+            ExitCode::from(EXIT_USAGE)
+        );
+    }
+
+    #[test]
+    fn exit_codes_are_distinct_failures() {
+        let codes = [
+            EXIT_APPLY_FAILED,
+            EXIT_USAGE,
+            EXIT_MONITORS_UNAVAILABLE,
+            EXIT_CONFIG_FAILED,
+        ];
+
+        for (i, code) in codes.iter().enumerate() {
+            assert_ne!(*code, 0);
+            assert!(!codes[i + 1..].contains(code));
+        }
+    }
+}
@@ -1,34 +1,254 @@
+use crate::backup;
+use crate::fsutil::write_atomic;
+use crate::migrate::{self, CONFIG_VERSION};
+use crate::monitors_conf;
+use crate::state::DisplayMode;
+use hyprland::data::Monitor;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Rotated backups kept of `config.json`, one is taken before every save
+const KEPT_BACKUPS: usize = 10;
+
+/// Location of `config.json`
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hyprland-display-switcher")
+        .join("config.json")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisplayConfig {
+    /// Format version, see `migrate`
+    #[serde(default)]
+    pub version: u32,
+    /// Named profiles, keyed by profile id
     pub extend_configurations: HashMap<String, ExtendConfiguration>,
     pub last_used_extend_config: Option<String>,
+    /// Default profile id for each monitor set, keyed by `monitor_set_key`
+    #[serde(default)]
+    pub default_profiles: HashMap<String, String>,
+    /// Mode the hotplug daemon applies when no saved configuration matches
+    #[serde(default)]
+    pub hotplug_fallback_mode: Option<DisplayMode>,
+    /// Output the switcher opens on when connected, instead of the focused one
+    #[serde(default)]
+    pub switcher_output: Option<String>,
+    /// Skip the desktop notification sent after a mode is applied headlessly
+    #[serde(default)]
+    pub disable_notifications: bool,
+    /// Write every applied layout to `monitors_conf_path`, so it survives
+    /// `hyprctl reload` and logging in again
+    #[serde(default)]
+    pub persist_layout: bool,
+    /// Where the layout is persisted, `~/.config/hypr/monitors.conf` when unset
+    #[serde(default)]
+    pub monitors_conf_path: Option<PathBuf>,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            extend_configurations: HashMap::new(),
+            last_used_extend_config: None,
+            default_profiles: HashMap::new(),
+            hotplug_fallback_mode: None,
+            switcher_output: None,
+            disable_notifications: false,
+            persist_layout: false,
+            monitors_conf_path: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtendConfiguration {
     pub name: String,
     pub primary_monitor: String,
-    pub primary_resolution: String,
-    pub primary_rotation: String,
-    pub secondary_monitor: String,
-    pub secondary_resolution: String,
-    pub secondary_rotation: String,
+    /// Monitors in the order they are laid out
+    pub monitors: Vec<MonitorConfiguration>,
     pub layout: ExtendLayout,
+    /// How monitors of different sizes line up across the layout direction
+    #[serde(default)]
+    pub alignment: ExtendAlignment,
+    /// Extra shift in logical pixels across the layout direction, applied to
+    /// every monitor after the first
+    #[serde(default)]
+    pub alignment_offset: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_used: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorConfiguration {
+    /// Connector name the monitor had when the profile was saved
+    pub name: String,
+    /// Hyprland's monitor description (make, model and serial), used to find
+    /// the monitor again when it moves to another connector
+    #[serde(default)]
+    pub description: String,
+    pub resolution: String,
+    /// Refresh rate in Hz, or `None` to let Hyprland pick one
+    #[serde(default)]
+    pub refresh_rate: Option<f32>,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub rotation: String,
+    /// Position in logical pixels, set when the layout is arranged by hand
+    #[serde(default)]
+    pub position: Option<(i32, i32)>,
+    /// Connector name of the monitor this one mirrors
+    #[serde(default)]
+    pub mirror: Option<String>,
+    /// Output that stays off, e.g. a closed laptop panel. It takes no part
+    /// in the layout.
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl ExtendConfiguration {
+    /// Maps the saved monitors onto the connected ones and returns a copy
+    /// that uses their current connector names. Monitors are matched by
+    /// description, falling back to the connector name only when the saved
+    /// description is missing or shared by several monitors.
+    pub fn resolve_for_monitors(&self, monitors: &[Monitor]) -> Option<ExtendConfiguration> {
+        if self.monitors.len() != monitors.len() {
+            return None;
+        }
+
+        let saved_descriptions: Vec<&str> = self
+            .monitors
+            .iter()
+            .map(|m| m.description.as_str())
+            .collect();
+        let current_descriptions: Vec<&str> =
+            monitors.iter().map(|m| m.description.as_str()).collect();
+
+        let mut matches: Vec<Option<usize>> = self
+            .monitors
+            .iter()
+            .map(|saved| {
+                if !is_unique_description(&saved.description, &saved_descriptions) {
+                    return None;
+                }
+                monitors.iter().position(|current| {
+                    current.description == saved.description
+                        && is_unique_description(&current.description, &current_descriptions)
+                })
+            })
+            .collect();
+
+        // Connector names only stand in for identities that are missing or
+        // ambiguous, and only claim monitors that no identity matched. A
+        // monitor with a unique identity that is not connected must not pick
+        // up whatever is plugged into its old port, and one of two identical
+        // monitors must not pick up a different model.
+        for (saved_index, saved) in self.monitors.iter().enumerate() {
+            if matches[saved_index].is_none()
+                && !is_unique_description(&saved.description, &saved_descriptions)
+            {
+                matches[saved_index] = monitors
+                    .iter()
+                    .enumerate()
+                    .find(|(index, current)| {
+                        current.name == saved.name
+                            && (saved.description.is_empty()
+                                || current.description == saved.description)
+                            && !matches.contains(&Some(*index))
+                    })
+                    .map(|(index, _)| index);
+            }
+        }
+
+        let mut resolved = self.clone();
+        for (saved, matched) in resolved.monitors.iter_mut().zip(&matches) {
+            let current = &monitors[(*matched)?];
+            if saved.name == self.primary_monitor {
+                resolved.primary_monitor = current.name.clone();
+            }
+            saved.name = current.name.clone();
+            saved.description = current.description.clone();
+        }
+
+        // Mirror sources refer to the saved connector names as well
+        let names: Vec<String> = resolved.monitors.iter().map(|m| m.name.clone()).collect();
+        for monitor in &mut resolved.monitors {
+            if let Some(mirror) = &mut monitor.mirror
+                && let Some(index) = self.monitors.iter().position(|m| &m.name == mirror)
+            {
+                *mirror = names[index].clone();
+            }
+        }
+
+        Some(resolved)
+    }
+
+    /// Whether the profile switches some of its monitors off, which makes
+    /// it a poor starting point for extending across all of them
+    pub fn has_disabled_monitors(&self) -> bool {
+        self.monitors.iter().any(|m| m.disabled)
+    }
+
+    /// Order-independent key for the monitor set the profile covers
+    pub fn monitor_set_key(&self) -> String {
+        identity_key(
+            self.monitors
+                .iter()
+                .map(|m| (m.name.as_str(), m.description.as_str()))
+                .collect(),
+        )
+    }
+}
+
+/// Order-independent key identifying a set of connected monitors
+pub fn monitor_set_key(monitors: &[Monitor]) -> String {
+    identity_key(
+        monitors
+            .iter()
+            .map(|m| (m.name.as_str(), m.description.as_str()))
+            .collect(),
+    )
+}
+
+fn identity_key(monitors: Vec<(&str, &str)>) -> String {
+    let descriptions: Vec<&str> = monitors
+        .iter()
+        .map(|(_, description)| *description)
+        .collect();
+    let mut identities: Vec<&str> = monitors
+        .iter()
+        .map(|(name, description)| {
+            if is_unique_description(description, &descriptions) {
+                *description
+            } else {
+                *name
+            }
+        })
+        .collect();
+    identities.sort_unstable();
+    identities.join(",")
+}
+
+fn is_unique_description(description: &str, descriptions: &[&str]) -> bool {
+    !description.is_empty() && descriptions.iter().filter(|d| **d == description).count() == 1
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExtendLayout {
     LeftToRight,
     RightToLeft,
     TopToBottom,
     BottomToTop,
+    /// Positions arranged in the layout editor
+    Custom,
 }
 
 impl std::fmt::Display for ExtendLayout {
@@ -38,13 +258,38 @@ impl std::fmt::Display for ExtendLayout {
             ExtendLayout::RightToLeft => write!(f, "Right to Left"),
             ExtendLayout::TopToBottom => write!(f, "Top to Bottom"),
             ExtendLayout::BottomToTop => write!(f, "Bottom to Top"),
+            ExtendLayout::Custom => write!(f, "Custom"),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ExtendAlignment {
+    /// Top edges for horizontal layouts, left edges for vertical ones
+    #[default]
+    Start,
+    Center,
+    /// Bottom edges for horizontal layouts, right edges for vertical ones
+    End,
+}
+
+impl std::fmt::Display for ExtendAlignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtendAlignment::Start => write!(f, "Start"),
+            ExtendAlignment::Center => write!(f, "Center"),
+            ExtendAlignment::End => write!(f, "End"),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ConfigManager {
     config_path: PathBuf,
     config: DisplayConfig,
+    /// Set when the file was written by a newer version, whose fields would
+    /// be lost by saving it from this one
+    read_only: bool,
 }
 
 impl Default for ConfigManager {
@@ -52,45 +297,106 @@ impl Default for ConfigManager {
         Self {
             config_path: PathBuf::from("config.json"),
             config: DisplayConfig::default(),
+            read_only: false,
         }
     }
 }
 
 impl ConfigManager {
     pub fn new() -> anyhow::Result<Self> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("hyprland-display-switcher");
-
-        fs::create_dir_all(&config_dir)?;
-
-        let config_path = config_dir.join("config.json");
+        let config_path = config_path();
+        if let Some(config_dir) = config_path.parent() {
+            fs::create_dir_all(config_dir)?;
+        }
 
-        let config = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
+        let first_run = !config_path.exists();
+        let mut migrated = false;
+        let mut read_only = false;
+        let config = if first_run {
             DisplayConfig::default()
+        } else {
+            let content = fs::read_to_string(&config_path)?;
+            match migrate::parse_config(&content) {
+                Ok(loaded) => {
+                    match loaded.original_version {
+                        Some(version) if version > CONFIG_VERSION => {
+                            eprintln!(
+                                "{} was written by a newer version (format {version}), \
+                                 changes will not be saved",
+                                config_path.display()
+                            );
+                            read_only = true;
+                        }
+                        // Saving in the current format has to be safe to undo.
+                        // The migrated file is saved right away, so this
+                        // happens once.
+                        Some(version) => {
+                            match backup::create(&config_path, &format!("v{version}")) {
+                                Ok(backup) => {
+                                    eprintln!("Backed up configuration to {}", backup.display());
+                                    migrated = true;
+                                }
+                                Err(e) => {
+                                    eprintln!("Not migrating configuration: {e}");
+                                    read_only = true;
+                                }
+                            }
+                        }
+                        None => {}
+                    }
+                    loaded.config
+                }
+                Err(e) => {
+                    // Move the unreadable file out of the way instead of
+                    // overwriting it with the first change
+                    eprintln!("Failed to parse {}: {e}", config_path.display());
+                    let backup = backup::move_aside(&config_path, "invalid").map_err(|e| {
+                        anyhow::anyhow!("{e}, refusing to replace the configuration")
+                    })?;
+                    eprintln!("Moved configuration to {}, starting over", backup.display());
+                    DisplayConfig::default()
+                }
+            }
         };
 
-        Ok(Self {
+        let config_manager = Self {
             config_path,
             config,
-        })
+            read_only,
+        };
+
+        if migrated {
+            config_manager.save_to_disk()?;
+        }
+
+        Ok(config_manager)
     }
 
-    pub fn save_extend_configuration(&mut self, config: ExtendConfiguration) -> anyhow::Result<()> {
-        let key = self.generate_config_key(&config);
+    /// Saves a profile, updating it in place when `profile_id` is given and
+    /// creating a new profile otherwise. Returns the profile id.
+    pub fn save_extend_configuration(
+        &mut self,
+        profile_id: Option<&str>,
+        config: ExtendConfiguration,
+    ) -> anyhow::Result<String> {
+        let key = match profile_id {
+            Some(id) => id.to_string(),
+            None => self.generate_profile_id(),
+        };
 
         let mut updated_config = config.clone();
         updated_config.last_used = chrono::Utc::now();
+        if let Some(existing) = self.config.extend_configurations.get(&key) {
+            updated_config.created_at = existing.created_at;
+        }
 
         self.config
             .extend_configurations
             .insert(key.clone(), updated_config);
-        self.config.last_used_extend_config = Some(key);
+        self.config.last_used_extend_config = Some(key.clone());
 
-        self.save_to_disk()
+        self.save_to_disk()?;
+        Ok(key)
     }
 
     // pub fn get_last_extend_configuration(&self) -> Option<&ExtendConfiguration> {
@@ -99,83 +405,394 @@ impl ConfigManager {
     //         .as_ref()
     //         .and_then(|key| self.config.extend_configurations.get(key))
     // }
-    //
-    // pub fn get_extend_configurations(&self) -> &HashMap<String, ExtendConfiguration> {
-    //     &self.config.extend_configurations
-    // }
-    //
-    // pub fn get_extend_configuration_by_monitors(
-    //     &self,
-    //     primary: &str,
-    //     secondary: &str,
-    // ) -> Option<&ExtendConfiguration> {
-    //     self.config.extend_configurations.values().find(|config| {
-    //         (config.primary_monitor == primary && config.secondary_monitor == secondary)
-    //             || (config.primary_monitor == secondary && config.secondary_monitor == primary)
-    //     })
-    // }
+
+    pub fn get_profile(&self, profile_id: &str) -> Option<&ExtendConfiguration> {
+        self.config.extend_configurations.get(profile_id)
+    }
+
+    /// Id of the profile with this name for the monitor set, see
+    /// `ExtendConfiguration::monitor_set_key`
+    pub fn find_profile_id(&self, name: &str, monitor_set_key: &str) -> Option<String> {
+        self.config
+            .extend_configurations
+            .iter()
+            .find(|(_, config)| config.name == name && config.monitor_set_key() == monitor_set_key)
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Profiles covering exactly the connected monitors, sorted by name and
+    /// resolved to the monitors' current connector names
+    pub fn get_profiles_for_monitors(
+        &self,
+        monitors: &[Monitor],
+    ) -> Vec<(&str, ExtendConfiguration)> {
+        let mut profiles: Vec<_> = self
+            .config
+            .extend_configurations
+            .iter()
+            .filter_map(|(id, config)| Some((id.as_str(), config.resolve_for_monitors(monitors)?)))
+            .collect();
+
+        profiles.sort_by(|a, b| a.1.name.cmp(&b.1.name).then(a.0.cmp(b.0)));
+        profiles
+    }
+
+    /// The default profile for the connected monitors, or the most recently
+    /// used one when no default has been chosen
+    pub fn find_profile_for_monitors(
+        &self,
+        monitors: &[Monitor],
+    ) -> Option<(&str, ExtendConfiguration)> {
+        if let Some(default_id) = self.config.default_profiles.get(&monitor_set_key(monitors))
+            && let Some((id, config)) = self.config.extend_configurations.get_key_value(default_id)
+            && let Some(resolved) = config.resolve_for_monitors(monitors)
+        {
+            return Some((id.as_str(), resolved));
+        }
+
+        self.get_profiles_for_monitors(monitors)
+            .into_iter()
+            .max_by_key(|(_, config)| config.last_used)
+    }
 
     pub fn get_extend_configuration_for_monitors(
         &self,
-        available_monitors: &[String],
-    ) -> Option<&ExtendConfiguration> {
-        // Find the most recent configuration that uses any of the available monitors
-        let mut matching_configs: Vec<_> = self
+        monitors: &[Monitor],
+    ) -> Option<ExtendConfiguration> {
+        self.find_profile_for_monitors(monitors)
+            .map(|(_, config)| config)
+    }
+
+    pub fn rename_profile(&mut self, profile_id: &str, name: String) -> anyhow::Result<()> {
+        let config = self
             .config
             .extend_configurations
+            .get_mut(profile_id)
+            .ok_or_else(|| anyhow::anyhow!("Profile {profile_id} not found"))?;
+        config.name = name;
+
+        self.save_to_disk()
+    }
+
+    pub fn duplicate_profile(&mut self, profile_id: &str) -> anyhow::Result<String> {
+        let mut config = self
+            .get_profile(profile_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Profile {profile_id} not found"))?;
+
+        let now = chrono::Utc::now();
+        config.name = format!("{} (copy)", config.name);
+        config.created_at = now;
+        config.last_used = now;
+
+        let key = self.generate_profile_id();
+        self.config
+            .extend_configurations
+            .insert(key.clone(), config);
+
+        self.save_to_disk()?;
+        Ok(key)
+    }
+
+    pub fn delete_profile(&mut self, profile_id: &str) -> anyhow::Result<()> {
+        self.config.extend_configurations.remove(profile_id);
+        self.config
+            .default_profiles
+            .retain(|_, default_id| default_id != profile_id);
+        if self.config.last_used_extend_config.as_deref() == Some(profile_id) {
+            self.config.last_used_extend_config = None;
+        }
+
+        self.save_to_disk()
+    }
+
+    /// Makes the profile the default for the connected monitors. The key is
+    /// built from those monitors, as `find_profile_for_monitors` looks it up,
+    /// so profiles saved without descriptions work as well.
+    pub fn set_default_profile(
+        &mut self,
+        profile_id: &str,
+        monitors: &[Monitor],
+    ) -> anyhow::Result<()> {
+        let config = self
+            .get_profile(profile_id)
+            .ok_or_else(|| anyhow::anyhow!("Profile {profile_id} not found"))?;
+        if config.resolve_for_monitors(monitors).is_none() {
+            anyhow::bail!("Profile {profile_id} does not match the connected monitors");
+        }
+        let key = monitor_set_key(monitors);
+
+        self.config
+            .default_profiles
+            .insert(key, profile_id.to_string());
+
+        self.save_to_disk()
+    }
+
+    pub fn is_default_profile(&self, profile_id: &str) -> bool {
+        self.config
+            .default_profiles
             .values()
-            .filter(|config| {
-                available_monitors.contains(&config.primary_monitor)
-                    && available_monitors.contains(&config.secondary_monitor)
-            })
-            .collect();
+            .any(|default_id| default_id == profile_id)
+    }
 
-        // Sort by last_used date (most recent first)
-        matching_configs.sort_by(|a, b| b.last_used.cmp(&a.last_used));
-        matching_configs.first().copied()
+    pub fn get_hotplug_fallback_mode(&self) -> Option<&DisplayMode> {
+        self.config.hotplug_fallback_mode.as_ref()
     }
 
-    fn generate_config_key(&self, config: &ExtendConfiguration) -> String {
-        format!("{}_{}", config.primary_monitor, config.secondary_monitor)
+    pub fn get_switcher_output(&self) -> Option<&str> {
+        self.config.switcher_output.as_deref()
     }
 
-    fn save_to_disk(&self) -> anyhow::Result<()> {
-        let content = serde_json::to_string_pretty(&self.config)?;
-        fs::write(&self.config_path, content)?;
-        Ok(())
+    pub fn notifications_enabled(&self) -> bool {
+        !self.config.disable_notifications
     }
 
-    pub fn get_preferred_primary_monitor(&self, available_monitors: &[String]) -> Option<String> {
-        if let Some(last_config) = self.get_extend_configuration_for_monitors(available_monitors)
-            && available_monitors.contains(&last_config.primary_monitor)
+    /// File applied layouts are written to, or `None` if they are not
+    /// persisted
+    pub fn get_monitors_conf_path(&self) -> Option<PathBuf> {
+        self.config.persist_layout.then(|| {
+            self.config
+                .monitors_conf_path
+                .clone()
+                .unwrap_or_else(monitors_conf::default_path)
+        })
+    }
+
+    fn generate_profile_id(&self) -> String {
+        let mut millis = chrono::Utc::now().timestamp_millis();
+        while self
+            .config
+            .extend_configurations
+            .contains_key(&format!("profile-{millis}"))
         {
-            return Some(last_config.primary_monitor.clone());
+            millis += 1;
         }
+        format!("profile-{millis}")
+    }
 
-        None
+    /// Whether nothing has been written yet
+    pub fn is_first_run(&self) -> bool {
+        !self.config_path.exists()
+    }
+
+    /// Writes the configuration atomically, after rotating a backup of the
+    /// previous version into `backups/`
+    pub fn save_to_disk(&self) -> anyhow::Result<()> {
+        if self.read_only {
+            anyhow::bail!(
+                "{} was written by a newer version or could not be backed up, not saving",
+                self.config_path.display()
+            );
+        }
+
+        let content = serde_json::to_string_pretty(&self.config)?;
+
+        if self.config_path.exists()
+            && let Err(e) = backup::create(&self.config_path, backup::ROTATED_TAG)
+                .and_then(|_| backup::prune(&self.config_path, KEPT_BACKUPS))
+        {
+            eprintln!("Failed to back up configuration: {e}");
+        }
+
+        write_atomic(&self.config_path, content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get_preferred_primary_monitor(&self, monitors: &[Monitor]) -> Option<String> {
+        self.get_extend_configuration_for_monitors(monitors)
+            .map(|last_config| last_config.primary_monitor)
+            .filter(|primary| monitors.iter().any(|m| &m.name == primary))
     }
 
     pub fn create_config_from_settings(
         primary_monitor: String,
-        secondary_monitor: String,
-        primary_resolution: String,
-        primary_rotation: String,
-        secondary_resolution: String,
-        secondary_rotation: String,
+        monitors: Vec<MonitorConfiguration>,
         layout: ExtendLayout,
+        alignment: ExtendAlignment,
+        alignment_offset: i32,
     ) -> ExtendConfiguration {
         let now = chrono::Utc::now();
         ExtendConfiguration {
-            name: format!("{primary_monitor} + {secondary_monitor}"),
+            name: monitors
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" + "),
             primary_monitor,
-            primary_resolution,
-            primary_rotation,
-            secondary_monitor,
-            secondary_resolution,
-            secondary_rotation,
+            monitors,
             layout,
+            alignment,
+            alignment_offset,
             created_at: now,
             last_used: now,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{DisplayBackend, FakeBackend};
+    use crate::display::{apply_single_output_mode, capture_current_layout};
+
+    fn temp_config_manager(name: &str) -> ConfigManager {
+        let dir = std::env::temp_dir().join(format!(
+            "display-switcher-config-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        ConfigManager {
+            config_path: dir.join("config.json"),
+            ..ConfigManager::default()
+        }
+    }
+
+    fn monitor(name: &str, description: &str) -> Monitor {
+        let mut monitor = FakeBackend::with_sample_monitors().monitors().unwrap()[0].clone();
+        monitor.name = name.to_string();
+        monitor.description = description.to_string();
+        monitor
+    }
+
+    /// Profile for the monitors, with the first one as primary
+    fn profile(monitors: &[(&str, &str)]) -> ExtendConfiguration {
+        ConfigManager::create_config_from_settings(
+            monitors[0].0.to_string(),
+            monitors
+                .iter()
+                .map(|(name, description)| MonitorConfiguration {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    resolution: "auto".to_string(),
+                    refresh_rate: None,
+                    scale: 1.0,
+                    rotation: "normal".to_string(),
+                    position: None,
+                    mirror: None,
+                    disabled: false,
+                })
+                .collect(),
+            ExtendLayout::LeftToRight,
+            ExtendAlignment::Start,
+            0,
+        )
+    }
+
+    fn resolved_names(config: &ExtendConfiguration, monitors: &[Monitor]) -> Option<Vec<String>> {
+        let resolved = config.resolve_for_monitors(monitors)?;
+        Some(resolved.monitors.into_iter().map(|m| m.name).collect())
+    }
+
+    #[test]
+    fn unique_monitors_are_found_on_other_ports() {
+        let config = profile(&[("DP-1", "Dell U2720Q"), ("DP-2", "LG 27UL850")]);
+        let monitors = [
+            monitor("DP-1", "LG 27UL850"),
+            monitor("DP-2", "Dell U2720Q"),
+        ];
+
+        let resolved = config.resolve_for_monitors(&monitors).unwrap();
+
+        let names: Vec<&str> = resolved.monitors.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["DP-2", "DP-1"]);
+        assert_eq!(resolved.primary_monitor, "DP-2");
+    }
+
+    #[test]
+    fn unique_monitors_do_not_match_by_port() {
+        let config = profile(&[("DP-1", "Dell U2720Q"), ("DP-2", "LG 27UL850")]);
+        let monitors = [
+            monitor("DP-1", "Samsung C27"),
+            monitor("DP-2", "LG 27UL850"),
+        ];
+
+        assert_eq!(resolved_names(&config, &monitors), None);
+    }
+
+    #[test]
+    fn duplicated_monitors_match_by_port() {
+        let config = profile(&[("DP-1", "Dell U2720Q"), ("DP-2", "Dell U2720Q")]);
+
+        let identical = [
+            monitor("DP-2", "Dell U2720Q"),
+            monitor("DP-1", "Dell U2720Q"),
+        ];
+        assert_eq!(
+            resolved_names(&config, &identical),
+            Some(vec!["DP-1".to_string(), "DP-2".to_string()])
+        );
+
+        let moved = [
+            monitor("DP-1", "Dell U2720Q"),
+            monitor("DP-3", "Dell U2720Q"),
+        ];
+        assert_eq!(resolved_names(&config, &moved), None);
+    }
+
+    #[test]
+    fn duplicated_monitors_need_the_same_model_on_the_port() {
+        let config = profile(&[("DP-1", "Dell U2720Q"), ("DP-2", "Dell U2720Q")]);
+        let monitors = [
+            monitor("DP-1", "Dell U2720Q"),
+            monitor("DP-2", "LG 27UL850"),
+        ];
+
+        assert_eq!(resolved_names(&config, &monitors), None);
+    }
+
+    #[test]
+    fn monitors_without_a_description_match_any_monitor_on_the_port() {
+        let config = profile(&[("eDP-1", ""), ("DP-1", "")]);
+
+        let monitors = [
+            monitor("eDP-1", "Laptop panel"),
+            monitor("DP-1", "LG 27UL850"),
+        ];
+        assert_eq!(
+            resolved_names(&config, &monitors),
+            Some(vec!["eDP-1".to_string(), "DP-1".to_string()])
+        );
+
+        let moved = [
+            monitor("eDP-1", "Laptop panel"),
+            monitor("DP-2", "LG 27UL850"),
+        ];
+        assert_eq!(resolved_names(&config, &moved), None);
+    }
+
+    #[test]
+    fn profiles_need_the_same_number_of_monitors() {
+        let config = profile(&[("DP-1", "Dell U2720Q"), ("DP-2", "LG 27UL850")]);
+
+        assert_eq!(
+            resolved_names(&config, &[monitor("DP-1", "Dell U2720Q")]),
+            None
+        );
+    }
+
+    #[test]
+    fn captured_layout_with_a_disabled_output_is_found_again() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+        apply_single_output_mode(&backend, &monitors, "DP-3").unwrap();
+        let monitors = backend.monitors().unwrap();
+        let mut config_manager = temp_config_manager("capture");
+
+        let config = capture_current_layout(&backend, &monitors);
+        let profile_id = config_manager
+            .save_extend_configuration(None, config)
+            .unwrap();
+
+        let profiles = config_manager.get_profiles_for_monitors(&monitors);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].0, profile_id);
+        assert_eq!(
+            config_manager
+                .find_profile_for_monitors(&monitors)
+                .map(|(id, _)| id.to_string()),
+            Some(profile_id)
+        );
+    }
+}
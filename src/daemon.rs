@@ -0,0 +1,108 @@
+use crate::backend::DisplayBackend;
+use crate::config::ConfigManager;
+use crate::display::{apply_display_mode, apply_extend_mode};
+use crate::import;
+use crate::ipc;
+use crate::notify;
+use crate::state::DisplayMode;
+use std::sync::Arc;
+
+struct HotplugState {
+    backend: Arc<dyn DisplayBackend>,
+    fallback_override: Option<DisplayMode>,
+    // Monitor set the daemon last reacted to. Applying a mode emits its own
+    // monitor events, so only act when the set actually changes.
+    last_seen: Vec<String>,
+}
+
+/// Listens for monitor hotplug events and re-applies the saved configuration
+/// for the connected monitors. Blocks until the event socket is closed.
+pub fn run(
+    backend: Arc<dyn DisplayBackend>,
+    fallback_override: Option<DisplayMode>,
+) -> Result<(), String> {
+    let mut state = HotplugState {
+        backend,
+        fallback_override,
+        last_seen: Vec::new(),
+    };
+
+    let mut events = ipc::EventStream::connect()?;
+
+    handle_monitor_change(&mut state);
+
+    eprintln!("Listening for monitor changes...");
+    while let Some(line) = events.next_event() {
+        if let Some(name) = line.strip_prefix("monitoradded>>") {
+            eprintln!("Monitor added: {name}");
+        } else if let Some(name) = line.strip_prefix("monitorremoved>>") {
+            eprintln!("Monitor removed: {name}");
+        } else {
+            continue;
+        }
+
+        handle_monitor_change(&mut state);
+    }
+
+    Err("Hyprland event socket closed".to_string())
+}
+
+fn handle_monitor_change(state: &mut HotplugState) {
+    let monitors = match state.backend.monitors() {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            eprintln!("Failed to query monitors: {e}");
+            return;
+        }
+    };
+
+    let mut monitor_names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
+    monitor_names.sort();
+    if state.last_seen == monitor_names {
+        return;
+    }
+
+    // Re-read the configuration so layouts saved from the GUI are picked up
+    let mut config_manager = match ConfigManager::new() {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            eprintln!("Failed to initialize config manager: {e}");
+            return;
+        }
+    };
+    import::import_on_first_run(&mut config_manager, &monitors);
+
+    let backend = &*state.backend;
+    let applied = if let Some(saved_config) =
+        config_manager.get_extend_configuration_for_monitors(&monitors)
+    {
+        eprintln!("Applying saved configuration: {}", saved_config.name);
+        Some((
+            format!("Extended: {}", saved_config.name),
+            apply_extend_mode(backend, &monitors, &saved_config),
+        ))
+    } else if let Some(mode) = state
+        .fallback_override
+        .as_ref()
+        .or(config_manager.get_hotplug_fallback_mode())
+    {
+        eprintln!("No saved configuration matches, applying fallback mode {mode:?}");
+        Some((
+            notify::mode_title(mode).to_string(),
+            apply_display_mode(backend, &monitors, mode, &config_manager),
+        ))
+    } else {
+        None
+    };
+
+    if let Some((title, result)) = applied {
+        if let Err(e) = &result {
+            eprintln!("Error applying display mode: {e}");
+        }
+        notify::notify_result(&config_manager, backend, &title, &result);
+    }
+
+    // Disabled outputs stay in the monitor list, so applying a mode leaves
+    // the set as it was queried
+    state.last_seen = monitor_names;
+}
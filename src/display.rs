@@ -1,15 +1,133 @@
-use crate::config::{ConfigManager, ExtendConfiguration, ExtendLayout};
-use hyprland::data::Monitor;
-use hyprland::dispatch::{Dispatch, DispatchType};
-use std::process::Command;
+use crate::backend::DisplayBackend;
+use crate::config::{
+    ConfigManager, ExtendAlignment, ExtendConfiguration, ExtendLayout, MonitorConfiguration,
+};
+use crate::monitor_rule::{MonitorMode, MonitorRule};
+use crate::state::DisplayMode;
+use hyprland::data::{Monitor, Transforms};
+
+pub fn apply_display_mode(
+    backend: &dyn DisplayBackend,
+    monitors: &[Monitor],
+    mode: &DisplayMode,
+    config_manager: &ConfigManager,
+) -> Result<(), String> {
+    match mode {
+        DisplayMode::Mirror => apply_mirror_mode(backend, monitors, config_manager),
+        DisplayMode::Extend => apply_saved_extend_mode(backend, monitors, config_manager),
+        DisplayMode::MainScreenOnly => {
+            apply_single_screen_mode(backend, monitors, true, config_manager)
+        }
+        DisplayMode::SecondScreenOnly => {
+            apply_single_screen_mode(backend, monitors, false, config_manager)
+        }
+    }
+}
+
+/// Applies the saved extend configuration for the connected monitors, or a
+/// left-to-right layout at native resolutions when none has been saved yet.
+pub fn apply_saved_extend_mode(
+    backend: &dyn DisplayBackend,
+    monitors: &[Monitor],
+    config_manager: &ConfigManager,
+) -> Result<(), String> {
+    if let Some(saved_config) = config_manager
+        .get_extend_configuration_for_monitors(monitors)
+        .filter(|config| !config.has_disabled_monitors())
+    {
+        return apply_extend_mode(backend, monitors, &saved_config);
+    }
+
+    if monitors.len() < 2 {
+        return Ok(());
+    }
+
+    let default_config = default_extend_configuration(monitors, config_manager);
+    apply_extend_mode(backend, monitors, &default_config)
+}
+
+/// Builds a left-to-right layout with the primary monitor first, followed by
+/// the remaining monitors in the order Hyprland reports them.
+pub fn default_extend_configuration(
+    monitors: &[Monitor],
+    config_manager: &ConfigManager,
+) -> ExtendConfiguration {
+    let primary_monitor = determine_primary_monitor(monitors, config_manager);
+
+    let ordered_monitors = std::iter::once(primary_monitor)
+        .chain(monitors.iter().filter(|m| m.name != primary_monitor.name))
+        .map(|m| MonitorConfiguration {
+            name: m.name.clone(),
+            description: m.description.clone(),
+            resolution: format!("{}x{}", m.width, m.height),
+            refresh_rate: None,
+            scale: 1.0,
+            position: None,
+            rotation: "normal".to_string(),
+            mirror: None,
+            disabled: false,
+        })
+        .collect();
+
+    ConfigManager::create_config_from_settings(
+        primary_monitor.name.clone(),
+        ordered_monitors,
+        ExtendLayout::LeftToRight,
+        ExtendAlignment::Start,
+        0,
+    )
+}
+
+/// Profile that reproduces the monitors exactly as they are configured right
+/// now, e.g. after arranging them with `hyprctl`. Monitors are ordered left
+/// to right and keep their positions; disabled outputs come last and stay
+/// off when the profile is applied.
+pub fn capture_current_layout(
+    backend: &dyn DisplayBackend,
+    monitors: &[Monitor],
+) -> ExtendConfiguration {
+    let mut ordered: Vec<&Monitor> = monitors.iter().collect();
+    ordered.sort_by_key(|m| (m.disabled, m.x, m.y));
+
+    let primary_monitor = ordered
+        .iter()
+        .filter(|m| !m.disabled)
+        .find(|m| (m.x, m.y) == (0, 0))
+        .or(ordered.iter().find(|m| !m.disabled))
+        .map(|m| m.name.clone())
+        .unwrap_or_default();
+
+    let monitor_configs = ordered
+        .iter()
+        .map(|m| MonitorConfiguration {
+            name: m.name.clone(),
+            description: m.description.clone(),
+            resolution: format!("{}x{}", m.width, m.height),
+            refresh_rate: Some(m.refresh_rate),
+            scale: m.scale,
+            rotation: transform_rotation(m.transform).to_string(),
+            position: Some((m.x, m.y)),
+            mirror: backend.mirror_source(&m.name),
+            disabled: m.disabled,
+        })
+        .collect();
+
+    let mut config = ConfigManager::create_config_from_settings(
+        primary_monitor,
+        monitor_configs,
+        ExtendLayout::Custom,
+        ExtendAlignment::Start,
+        0,
+    );
+    config.name = format!("Layout {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    config
+}
 
 pub fn determine_primary_monitor<'a>(
     monitors: &'a [Monitor],
     config_manager: &ConfigManager,
 ) -> &'a Monitor {
-    let monitor_names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
-
-    if let Some(preferred_primary) = config_manager.get_preferred_primary_monitor(&monitor_names)
+    if let Some(preferred_primary) = config_manager.get_preferred_primary_monitor(monitors)
         && let Some(monitor) = monitors.iter().find(|m| m.name == preferred_primary)
     {
         return monitor;
@@ -27,6 +145,7 @@ fn determine_secondary_monitor<'a>(
 }
 
 pub fn apply_mirror_mode(
+    backend: &dyn DisplayBackend,
     monitors: &[Monitor],
     config_manager: &crate::config::ConfigManager,
 ) -> Result<(), String> {
@@ -35,132 +154,117 @@ pub fn apply_mirror_mode(
     }
 
     let primary_mon = determine_primary_monitor(monitors, config_manager);
-    let secondary_mon = monitors
-        .iter()
-        .find(|m| m.name != primary_mon.name)
-        .ok_or("Secondary monitor not found")?;
 
-    // Configure primary monitor
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},{}x{},0x0,{}\"",
-        primary_mon.name, primary_mon.width, primary_mon.height, primary_mon.scale
-    )))
-    .map_err(|e| e.to_string())?;
-
-    // Configure secondary monitor to mirror primary
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},{}x{},0x0,{},mirror,{}\"",
-        secondary_mon.name,
-        primary_mon.width,
-        primary_mon.height,
-        primary_mon.scale,
-        primary_mon.name
-    )))
-    .map_err(|e| e.to_string())?;
-
-    Ok(())
+    let primary_rule = MonitorRule {
+        scale: primary_mon.scale,
+        ..MonitorRule::new(
+            &primary_mon.name,
+            MonitorMode::resolution(primary_mon.width as i32, primary_mon.height as i32),
+            (0, 0),
+        )
+    };
+
+    // Configure every other monitor to mirror primary
+    let mirror_rules = monitors
+        .iter()
+        .filter(|m| m.name != primary_mon.name)
+        .map(|mirrored_mon| MonitorRule {
+            name: mirrored_mon.name.clone(),
+            mirror: Some(primary_mon.name.clone()),
+            ..primary_rule.clone()
+        });
+
+    let rules: Vec<MonitorRule> = std::iter::once(primary_rule.clone())
+        .chain(mirror_rules)
+        .collect();
+    backend.apply_monitor_rules(&rules)
 }
 
-pub fn apply_extend_mode(monitors: &[Monitor], config: &ExtendConfiguration) -> Result<(), String> {
+pub fn apply_extend_mode(
+    backend: &dyn DisplayBackend,
+    monitors: &[Monitor],
+    config: &ExtendConfiguration,
+) -> Result<(), String> {
     if monitors.len() < 2 {
         return Ok(());
     }
 
-    // Find monitors by name
-    let primary_monitor = monitors
-        .iter()
-        .find(|m| m.name == config.primary_monitor)
-        .ok_or("Primary monitor not found")?;
-
-    let _secondary_monitor = monitors
-        .iter()
-        .find(|m| m.name == config.secondary_monitor)
-        .ok_or("Secondary monitor not found")?;
-
-    // Calculate positions based on resolutions and layout
-    let primary_resolution = if config.primary_resolution == "auto" {
-        format!("{}x{}", primary_monitor.width, primary_monitor.height)
-    } else {
-        config.primary_resolution.clone()
+    // Disabled outputs are left out of the layout and switched off last
+    let (disabled, enabled): (Vec<_>, Vec<_>) =
+        config.monitors.iter().cloned().partition(|m| m.disabled);
+    let config = &ExtendConfiguration {
+        monitors: enabled,
+        ..config.clone()
     };
 
-    // Parse dimensions for positioning calculations
-    let primary_width = if config.primary_resolution == "auto" {
-        primary_monitor.width as i32
-    } else {
-        primary_resolution
-            .split('x')
-            .next()
-            .unwrap_or("1920")
-            .parse()
-            .unwrap_or(1920)
-    };
-
-    let primary_height = if config.primary_resolution == "auto" {
-        primary_monitor.height as i32
-    } else {
-        primary_resolution
-            .split('x')
-            .nth(1)
-            .unwrap_or("1080")
-            .parse()
-            .unwrap_or(1080)
-    };
-
-    let (primary_pos, secondary_pos) = calculate_positions(
-        &config.layout,
-        primary_width,
-        primary_height,
-        &config.secondary_resolution,
-    );
+    // Resolve the configured size of every monitor for positioning calculations
+    let mut sizes = Vec::with_capacity(config.monitors.len());
+    for monitor_config in &config.monitors {
+        let monitor = monitors
+            .iter()
+            .find(|m| m.name == monitor_config.name)
+            .ok_or_else(|| format!("Monitor {} not found", monitor_config.name))?;
 
-    // Build transform strings
-    let primary_transform = get_transform_string(&config.primary_rotation);
-    let secondary_transform = get_transform_string(&config.secondary_rotation);
-
-    // Build commands
-    let primary_command = format!(
-        "hyprctl keyword monitor \"{},{}{}\"",
-        config.primary_monitor,
-        if config.primary_resolution == "auto" {
-            format!("auto,{primary_pos},1")
+        sizes.push(if monitor_config.resolution == "auto" {
+            (monitor.width as i32, monitor.height as i32)
         } else {
-            format!("{},{},1", config.primary_resolution, primary_pos)
-        },
-        primary_transform
-    );
+            parse_resolution(&monitor_config.resolution)
+        });
+    }
 
-    let secondary_command = format!(
-        "hyprctl keyword monitor \"{},{},{},1{}\"",
-        config.secondary_monitor, config.secondary_resolution, secondary_pos, secondary_transform
-    );
+    let positions = match custom_positions(config) {
+        Some(positions) => positions,
+        None => {
+            let logical_sizes: Vec<(i32, i32)> = config
+                .monitors
+                .iter()
+                .zip(&sizes)
+                .map(|(monitor_config, &size)| {
+                    logical_size(size, &monitor_config.rotation, monitor_config.scale)
+                })
+                .collect();
+            calculate_positions(
+                &config.layout,
+                config.alignment,
+                config.alignment_offset,
+                &logical_sizes,
+            )
+        }
+    };
 
-    eprintln!("Primary command: {primary_command}");
-    eprintln!("Secondary command: {secondary_command}");
-
-    // Disable both monitors first to reset their state
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},disable\"",
-        config.primary_monitor
-    )))
-    .map_err(|e| e.to_string())?;
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},disable\"",
-        config.secondary_monitor
-    )))
-    .map_err(|e| e.to_string())?;
-
-    // Wait for the changes to take effect
-    std::thread::sleep(std::time::Duration::from_millis(1000));
-
-    // Apply both monitor configurations
-    Dispatch::call(DispatchType::Exec(&primary_command)).map_err(|e| e.to_string())?;
-    Dispatch::call(DispatchType::Exec(&secondary_command)).map_err(|e| e.to_string())?;
-
-    Ok(())
+    // Build rules
+    let rules: Vec<MonitorRule> = config
+        .monitors
+        .iter()
+        .zip(sizes.iter().zip(&positions))
+        .map(
+            |(monitor_config, (&(width, height), &position))| MonitorRule {
+                scale: monitor_config.scale,
+                transform: rotation_transform(&monitor_config.rotation),
+                mirror: monitor_config.mirror.clone(),
+                ..MonitorRule::new(
+                    &monitor_config.name,
+                    MonitorMode::Resolution {
+                        width,
+                        height,
+                        refresh_rate: monitor_config.refresh_rate,
+                    },
+                    position,
+                )
+            },
+        )
+        .chain(disabled.iter().map(|m| MonitorRule::disable(&m.name)))
+        .collect();
+
+    for rule in &rules {
+        eprintln!("Monitor rule: {rule}");
+    }
+
+    backend.apply_monitor_rules(&rules)
 }
 
 pub fn apply_single_screen_mode(
+    backend: &dyn DisplayBackend,
     monitors: &[Monitor],
     primary_only: bool,
     config_manager: &ConfigManager,
@@ -173,164 +277,529 @@ pub fn apply_single_screen_mode(
     let secondary_monitor = determine_secondary_monitor(monitors, primary_monitor)
         .ok_or("Secondary monitor not found")?;
 
-    let (active_mon, inactive_mon) = if primary_only {
-        (primary_monitor, secondary_monitor)
+    let active_mon = if primary_only {
+        primary_monitor
     } else {
-        (secondary_monitor, primary_monitor)
+        secondary_monitor
     };
 
+    apply_single_output_mode(backend, monitors, &active_mon.name)
+}
+
+/// Keeps only the named monitor enabled and disables every other output.
+pub fn apply_single_output_mode(
+    backend: &dyn DisplayBackend,
+    monitors: &[Monitor],
+    active_name: &str,
+) -> Result<(), String> {
+    let active_mon = monitors
+        .iter()
+        .find(|m| m.name == active_name)
+        .ok_or_else(|| format!("Monitor {active_name} not found"))?;
+
     // Enable active monitor with its native resolution
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},{}x{},0x0,{}\"",
-        active_mon.name, active_mon.width, active_mon.height, active_mon.scale
-    )))
-    .map_err(|e| e.to_string())?;
-
-    // Disable inactive monitor
-    Dispatch::call(DispatchType::Exec(&format!(
-        "hyprctl keyword monitor \"{},disable\"",
-        inactive_mon.name
-    )))
-    .map_err(|e| e.to_string())?;
-
-    Ok(())
+    let active_rule = MonitorRule {
+        scale: active_mon.scale,
+        ..MonitorRule::new(
+            &active_mon.name,
+            MonitorMode::resolution(active_mon.width as i32, active_mon.height as i32),
+            (0, 0),
+        )
+    };
+
+    // Disable inactive monitors
+    let inactive_rules = monitors
+        .iter()
+        .filter(|m| m.name != active_mon.name)
+        .map(|inactive_mon| MonitorRule::disable(&inactive_mon.name));
+
+    let rules: Vec<MonitorRule> = std::iter::once(active_rule).chain(inactive_rules).collect();
+    backend.apply_monitor_rules(&rules)
 }
 
-pub fn reset_to_defaults() -> Result<(), String> {
-    Dispatch::call(DispatchType::Exec("hyprctl reload")).map_err(|e| e.to_string())?;
-    Ok(())
+pub fn reset_to_defaults(backend: &dyn DisplayBackend) -> Result<(), String> {
+    backend.reload()
 }
 
-pub fn get_monitor_available_modes(monitor_name: &str) -> Vec<String> {
-    let output = Command::new("hyprctl").args(["monitors", "all"]).output();
+/// Mirroring monitor and the monitor it mirrors, for every monitor that
+/// mirrors another one
+pub fn mirror_sources(backend: &dyn DisplayBackend, monitors: &[Monitor]) -> Vec<(String, String)> {
+    monitors
+        .iter()
+        .filter_map(|m| Some((m.name.clone(), backend.mirror_source(&m.name)?)))
+        .collect()
+}
 
-    match output {
-        Ok(output) if output.status.success() => {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let modes = parse_monitor_modes(&output_str, monitor_name);
-            eprintln!("Available modes for {monitor_name}: {modes:?}");
-            modes
-        }
-        _ => {
-            eprintln!("Failed to get modes for {monitor_name}, using fallback");
-            // Fallback to common resolutions if hyprctl fails
-            vec![
-                "1920x1080".to_string(),
-                "2560x1440".to_string(),
-                "3840x2160".to_string(),
-                "1680x1050".to_string(),
-                "1366x768".to_string(),
-                "1440x900".to_string(),
-            ]
-        }
+/// Restores monitors to a state captured with `DisplayBackend::monitors` and
+/// `mirror_sources` before a mode change. Outputs that were disabled at the
+/// time are disabled again, and mirrors mirror again.
+pub fn restore_monitor_state(
+    backend: &dyn DisplayBackend,
+    previous_monitors: &[Monitor],
+    previous_mirrors: &[(String, String)],
+) -> Result<(), String> {
+    if previous_monitors.is_empty() {
+        return Err("No previous monitor state to restore".to_string());
     }
+
+    // Re-enable the previous outputs first so at least one stays active
+    let mut restore_rules: Vec<MonitorRule> = previous_monitors
+        .iter()
+        .map(|monitor| MonitorRule {
+            mirror: previous_mirrors
+                .iter()
+                .find(|(mirroring, _)| mirroring == &monitor.name)
+                .map(|(_, source)| source.clone()),
+            ..MonitorRule::from_monitor(monitor)
+        })
+        .collect();
+    restore_rules.sort_by_key(|rule| rule.disabled);
+
+    let disable_rules = backend
+        .monitors()?
+        .into_iter()
+        .filter(|monitor| !previous_monitors.iter().any(|m| m.name == monitor.name))
+        .map(|monitor| MonitorRule::disable(monitor.name));
+
+    let rules: Vec<MonitorRule> = restore_rules.into_iter().chain(disable_rules).collect();
+    backend.apply_monitor_rules(&rules)
 }
 
-fn calculate_positions(
+pub fn parse_resolution(resolution: &str) -> (i32, i32) {
+    let mut parts = resolution.split('x');
+    let width = parts.next().and_then(|w| w.parse().ok()).unwrap_or(1920);
+    let height = parts.next().and_then(|h| h.parse().ok()).unwrap_or(1080);
+    (width, height)
+}
+
+/// Size of an output in logical pixels, which is what Hyprland positions
+/// are measured in: rotated and divided by the scale
+pub fn logical_size((width, height): (i32, i32), rotation: &str, scale: f32) -> (i32, i32) {
+    let (width, height) = if is_quarter_turn(rotation_transform(rotation)) {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    (
+        (width as f32 / scale).round() as i32,
+        (height as f32 / scale).round() as i32,
+    )
+}
+
+/// Positions arranged in the layout editor, moved so the layout starts at
+/// 0x0. `None` unless the layout is custom and every monitor has a position.
+fn custom_positions(config: &ExtendConfiguration) -> Option<Vec<(i32, i32)>> {
+    if config.layout != ExtendLayout::Custom {
+        return None;
+    }
+
+    let positions: Vec<(i32, i32)> = config
+        .monitors
+        .iter()
+        .map(|m| m.position)
+        .collect::<Option<_>>()?;
+    let min_x = positions.iter().map(|&(x, _)| x).min()?;
+    let min_y = positions.iter().map(|&(_, y)| y).min()?;
+
+    Some(
+        positions
+            .into_iter()
+            .map(|(x, y)| (x - min_x, y - min_y))
+            .collect(),
+    )
+}
+
+/// Places monitors one after another along the layout direction, in the order
+/// given. For right-to-left and bottom-to-top the first monitor ends up last;
+/// custom layouts fall back to left-to-right. Across the layout direction
+/// monitors are aligned against the largest one, and every monitor after the
+/// first is shifted by the alignment offset.
+pub fn calculate_positions(
     layout: &ExtendLayout,
-    primary_width: i32,
-    primary_height: i32,
-    secondary_resolution: &str,
-) -> (String, String) {
-    match layout {
-        ExtendLayout::LeftToRight => ("0x0".to_string(), format!("{primary_width}x0")),
-        ExtendLayout::RightToLeft => {
-            let secondary_width = secondary_resolution
-                .split('x')
-                .next()
-                .unwrap_or("1920")
-                .parse::<i32>()
-                .unwrap_or(1920);
-            (format!("{secondary_width}x0"), "0x0".to_string())
-        }
-        ExtendLayout::TopToBottom => ("0x0".to_string(), format!("0x{primary_height}")),
-        ExtendLayout::BottomToTop => {
-            let secondary_height = secondary_resolution
-                .split('x')
-                .nth(1)
-                .unwrap_or("1080")
-                .parse::<i32>()
-                .unwrap_or(1080);
-            (format!("0x{secondary_height}"), "0x0".to_string())
+    alignment: ExtendAlignment,
+    alignment_offset: i32,
+    sizes: &[(i32, i32)],
+) -> Vec<(i32, i32)> {
+    let horizontal = matches!(
+        layout,
+        ExtendLayout::LeftToRight | ExtendLayout::RightToLeft | ExtendLayout::Custom
+    );
+    let reversed = matches!(
+        layout,
+        ExtendLayout::RightToLeft | ExtendLayout::BottomToTop
+    );
+
+    // (along, across) the layout direction
+    let extents: Vec<(i32, i32)> = sizes
+        .iter()
+        .map(|&(width, height)| {
+            if horizontal {
+                (width, height)
+            } else {
+                (height, width)
+            }
+        })
+        .collect();
+    let max_across = extents.iter().map(|&(_, across)| across).max().unwrap_or(0);
+
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    if reversed {
+        order.reverse();
+    }
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    let mut offset = 0;
+    for index in order {
+        let (along, across) = extents[index];
+        let mut cross = match alignment {
+            ExtendAlignment::Start => 0,
+            ExtendAlignment::Center => (max_across - across) / 2,
+            ExtendAlignment::End => max_across - across,
+        };
+        if index > 0 {
+            cross += alignment_offset;
         }
+
+        positions[index] = if horizontal {
+            (offset, cross)
+        } else {
+            (cross, offset)
+        };
+        offset += along;
     }
+
+    positions
 }
 
-fn get_transform_string(rotation: &str) -> &'static str {
+/// Rotation names offered in the settings, the plain rotations first. See
+/// `rotation_transform` for the Hyprland transform each one stands for.
+pub const ROTATIONS: [&str; 8] = [
+    "normal",
+    "left",
+    "right",
+    "inverted",
+    "flipped",
+    "flipped-left",
+    "flipped-right",
+    "flipped-inverted",
+];
+
+fn rotation_transform(rotation: &str) -> Transforms {
     match rotation {
-        "left" => ",transform,1",
-        "right" => ",transform,3",
-        "inverted" => ",transform,2",
-        _ => "",
+        "left" => Transforms::Normal90,
+        "right" => Transforms::Normal270,
+        "inverted" => Transforms::Normal180,
+        "flipped" => Transforms::Flipped,
+        "flipped-left" => Transforms::Flipped90,
+        "flipped-right" => Transforms::Flipped270,
+        "flipped-inverted" => Transforms::Flipped180,
+        _ => Transforms::Normal,
+    }
+}
+
+/// Inverse of `rotation_transform`, the `ROTATIONS` entry for a transform
+pub fn transform_rotation(transform: Transforms) -> &'static str {
+    match transform {
+        Transforms::Normal => "normal",
+        Transforms::Normal90 => "left",
+        Transforms::Normal180 => "inverted",
+        Transforms::Normal270 => "right",
+        Transforms::Flipped => "flipped",
+        Transforms::Flipped90 => "flipped-left",
+        Transforms::Flipped180 => "flipped-inverted",
+        Transforms::Flipped270 => "flipped-right",
     }
 }
 
-fn parse_monitor_modes(output: &str, target_monitor: &str) -> Vec<String> {
-    let mut modes = Vec::new();
-    let mut in_target_monitor = false;
+/// Whether the transform turns the output by 90 or 270 degrees, swapping its
+/// width and height
+fn is_quarter_turn(transform: Transforms) -> bool {
+    transform as u8 % 2 == 1
+}
 
-    for line in output.lines() {
-        let trimmed = line.trim();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
 
-        // Check if we're entering the target monitor section
-        if trimmed.starts_with(&format!("Monitor {target_monitor}")) {
-            in_target_monitor = true;
-            continue;
-        }
+    fn monitor<'a>(monitors: &'a [Monitor], name: &str) -> &'a Monitor {
+        monitors.iter().find(|m| m.name == name).unwrap()
+    }
 
-        // Check if we're leaving the current monitor section
-        if in_target_monitor
-            && trimmed.starts_with("Monitor ")
-            && !trimmed.starts_with(&format!("Monitor {target_monitor}"))
-        {
-            break;
-        }
+    #[test]
+    fn logical_size_swaps_sides_for_quarter_turns() {
+        assert_eq!(logical_size((2560, 1440), "normal", 1.0), (2560, 1440));
+        assert_eq!(logical_size((2560, 1440), "left", 1.0), (1440, 2560));
+        assert_eq!(logical_size((2560, 1440), "right", 1.0), (1440, 2560));
+        assert_eq!(logical_size((2560, 1440), "inverted", 1.0), (2560, 1440));
+        assert_eq!(
+            logical_size((2560, 1440), "flipped-left", 1.0),
+            (1440, 2560)
+        );
+    }
 
-        // Look for the available modes section
-        if in_target_monitor && trimmed.starts_with("availableModes:") {
-            // Extract modes from the same line: "availableModes: 1920x1080@60.00Hz ..."
-            let modes_str = trimmed.strip_prefix("availableModes:").unwrap_or("").trim();
-            for mode_str in modes_str.split_whitespace() {
-                if let Some(resolution) = extract_resolution_from_line(mode_str)
-                    && !modes.contains(&resolution)
-                {
-                    modes.push(resolution);
-                }
-            }
-            break; // We found the modes line, we're done
+    #[test]
+    fn logical_size_divides_by_the_scale() {
+        assert_eq!(logical_size((2560, 1600), "normal", 1.6), (1600, 1000));
+        assert_eq!(logical_size((3840, 2160), "left", 1.5), (1440, 2560));
+        assert_eq!(logical_size((1920, 1200), "normal", 1.25), (1536, 960));
+    }
+
+    #[test]
+    fn rotations_round_trip_through_transforms() {
+        for rotation in ROTATIONS {
+            assert_eq!(transform_rotation(rotation_transform(rotation)), rotation);
         }
     }
 
-    // If no modes found, provide fallback
-    if modes.is_empty() {
-        modes = vec![
-            "1920x1080".to_string(),
-            "2560x1440".to_string(),
-            "3840x2160".to_string(),
+    #[test]
+    fn portrait_secondary_is_placed_by_its_rotated_width() {
+        let sizes = [
+            logical_size((1920, 1080), "normal", 1.0),
+            logical_size((2560, 1440), "left", 1.0),
         ];
+
+        let positions = calculate_positions(
+            &ExtendLayout::LeftToRight,
+            ExtendAlignment::Start,
+            0,
+            &sizes,
+        );
+
+        assert_eq!(positions, [(0, 0), (1920, 0)]);
+
+        let positions = calculate_positions(
+            &ExtendLayout::RightToLeft,
+            ExtendAlignment::Start,
+            0,
+            &sizes,
+        );
+
+        assert_eq!(positions, [(1440, 0), (0, 0)]);
     }
 
-    modes
-}
+    #[test]
+    fn vertical_layouts_stack_by_rotated_height() {
+        let sizes = [
+            logical_size((2560, 1440), "left", 1.0),
+            logical_size((1920, 1080), "normal", 1.0),
+        ];
 
-fn extract_resolution_from_line(line: &str) -> Option<String> {
-    // Look for pattern like "1920x1080@60.00" or "  1920x1080@60.00hz"
-    if let Some(at_pos) = line.find('@') {
-        let before_at = &line[..at_pos];
-        if let Some(_x_pos) = before_at.rfind('x') {
-            let resolution_part = before_at.trim();
-            // Find the start of the resolution (numbers)
-            let start = resolution_part
-                .chars()
-                .position(|c| c.is_ascii_digit())
-                .unwrap_or(0);
-            let resolution = &resolution_part[start..];
-            if resolution.contains('x')
-                && resolution.chars().all(|c| c.is_ascii_digit() || c == 'x')
-            {
-                return Some(resolution.to_string());
-            }
-        }
+        let positions = calculate_positions(
+            &ExtendLayout::TopToBottom,
+            ExtendAlignment::Start,
+            0,
+            &sizes,
+        );
+
+        assert_eq!(positions, [(0, 0), (0, 2560)]);
+    }
+
+    /// A 1920x1080 monitor next to a 2560x1440 one
+    const MIXED_SIZES: [(i32, i32); 2] = [(1920, 1080), (2560, 1440)];
+
+    #[test]
+    fn start_alignment_lines_up_top_edges() {
+        let positions = calculate_positions(
+            &ExtendLayout::LeftToRight,
+            ExtendAlignment::Start,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(0, 0), (1920, 0)]);
+    }
+
+    #[test]
+    fn center_alignment_centres_smaller_monitors() {
+        let positions = calculate_positions(
+            &ExtendLayout::LeftToRight,
+            ExtendAlignment::Center,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(0, 180), (1920, 0)]);
+    }
+
+    #[test]
+    fn end_alignment_lines_up_bottom_edges() {
+        let positions = calculate_positions(
+            &ExtendLayout::LeftToRight,
+            ExtendAlignment::End,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(0, 360), (1920, 0)]);
+    }
+
+    #[test]
+    fn vertical_layouts_align_across_the_width() {
+        let positions = calculate_positions(
+            &ExtendLayout::BottomToTop,
+            ExtendAlignment::End,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(640, 1440), (0, 0)]);
+    }
+
+    #[test]
+    fn reversed_layouts_put_the_first_monitor_last() {
+        let positions = calculate_positions(
+            &ExtendLayout::RightToLeft,
+            ExtendAlignment::Start,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(2560, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn offset_shifts_every_monitor_after_the_first() {
+        let sizes = [(1920, 1080), (2560, 1440), (1920, 1080)];
+
+        let positions = calculate_positions(
+            &ExtendLayout::LeftToRight,
+            ExtendAlignment::Center,
+            -100,
+            &sizes,
+        );
+
+        assert_eq!(positions, [(0, 180), (1920, -100), (4480, 80)]);
+    }
+
+    #[test]
+    fn custom_layouts_are_placed_left_to_right() {
+        let positions = calculate_positions(
+            &ExtendLayout::Custom,
+            ExtendAlignment::Start,
+            0,
+            &MIXED_SIZES,
+        );
+
+        assert_eq!(positions, [(0, 0), (1920, 0)]);
+    }
+
+    #[test]
+    fn single_output_mode_disables_the_other_monitors() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+
+        apply_single_output_mode(&backend, &monitors, "DP-3").unwrap();
+
+        let applied = backend.monitors().unwrap();
+        assert!(monitor(&applied, "eDP-1").disabled);
+        let external = monitor(&applied, "DP-3");
+        assert!(!external.disabled);
+        assert_eq!((external.x, external.y), (0, 0));
+    }
+
+    #[test]
+    fn extend_mode_places_monitors_side_by_side() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+        let config = default_extend_configuration(&monitors, &ConfigManager::default());
+
+        apply_extend_mode(&backend, &monitors, &config).unwrap();
+
+        let applied = backend.monitors().unwrap();
+        assert_eq!(monitor(&applied, "eDP-1").x, 0);
+        assert_eq!(monitor(&applied, "DP-3").x, 1920);
+    }
+
+    #[test]
+    fn apply_fails_for_unknown_monitors() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+
+        assert!(apply_single_output_mode(&backend, &monitors, "HDMI-A-1").is_err());
+        assert_eq!(backend.monitors().unwrap(), monitors);
+    }
+
+    #[test]
+    fn restore_monitor_state_reverts_an_applied_mode() {
+        let backend = FakeBackend::with_sample_monitors();
+        let previous = backend.monitors().unwrap();
+        apply_single_output_mode(&backend, &previous, "DP-3").unwrap();
+
+        restore_monitor_state(&backend, &previous, &[]).unwrap();
+
+        assert_eq!(backend.monitors().unwrap(), previous);
+    }
+
+    #[test]
+    fn restore_monitor_state_keeps_disabled_outputs_off() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+        apply_single_output_mode(&backend, &monitors, "eDP-1").unwrap();
+        let previous = backend.monitors().unwrap();
+        apply_extend_mode(
+            &backend,
+            &previous,
+            &default_extend_configuration(&monitors, &ConfigManager::default()),
+        )
+        .unwrap();
+
+        restore_monitor_state(&backend, &previous, &[]).unwrap();
+
+        let restored = backend.monitors().unwrap();
+        assert!(monitor(&restored, "DP-3").disabled);
+        assert!(!monitor(&restored, "eDP-1").disabled);
+    }
+
+    #[test]
+    fn restore_monitor_state_mirrors_again() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+        apply_mirror_mode(&backend, &monitors, &ConfigManager::default()).unwrap();
+        let previous = backend.monitors().unwrap();
+        let previous_mirrors = mirror_sources(&backend, &previous);
+        assert_eq!(
+            previous_mirrors,
+            [("DP-3".to_string(), "eDP-1".to_string())]
+        );
+        apply_extend_mode(
+            &backend,
+            &previous,
+            &default_extend_configuration(&monitors, &ConfigManager::default()),
+        )
+        .unwrap();
+
+        restore_monitor_state(&backend, &previous, &previous_mirrors).unwrap();
+
+        assert_eq!(backend.mirror_source("DP-3").as_deref(), Some("eDP-1"));
+        assert_eq!(backend.monitors().unwrap(), previous);
+    }
+
+    #[test]
+    fn restore_monitor_state_needs_a_previous_state() {
+        let backend = FakeBackend::with_sample_monitors();
+        assert!(restore_monitor_state(&backend, &[], &[]).is_err());
+    }
+
+    #[test]
+    fn capture_current_layout_keeps_disabled_monitors_off() {
+        let backend = FakeBackend::with_sample_monitors();
+        let monitors = backend.monitors().unwrap();
+        apply_single_output_mode(&backend, &monitors, "DP-3").unwrap();
+        let captured = backend.monitors().unwrap();
+
+        let config = capture_current_layout(&backend, &captured);
+
+        let names: Vec<(&str, bool)> = config
+            .monitors
+            .iter()
+            .map(|m| (m.name.as_str(), m.disabled))
+            .collect();
+        assert_eq!(names, [("DP-3", false), ("eDP-1", true)]);
+        assert_eq!(config.primary_monitor, "DP-3");
+
+        backend.reload().unwrap();
+        apply_extend_mode(&backend, &monitors, &config).unwrap();
+        assert_eq!(backend.monitors().unwrap(), captured);
     }
-    None
 }
@@ -0,0 +1,168 @@
+use crate::backend::default_backend;
+use crate::ui;
+use hyprland::data::Monitor;
+use iced::widget::{Column, container, text};
+use iced::{Element, Event, Length, Task, Theme, alignment, event, keyboard, mouse, time};
+use iced_layershell::reexport::{Anchor, KeyboardInteractivity, Layer};
+use iced_layershell::settings::{LayerShellSettings, Settings, StartMode};
+use iced_layershell::{Application, to_layer_message};
+use std::process::{self, Child, ExitCode};
+use std::time::Duration;
+
+/// How long the labels stay up unless dismissed earlier
+const IDENTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Shows a label on every monitor by starting one overlay process per output,
+/// since a layer-shell surface is bound to a single output.
+pub fn spawn_overlays(monitors: &[Monitor]) -> Vec<Child> {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Failed to locate executable for identify overlays: {e}");
+            return Vec::new();
+        }
+    };
+
+    // Disabled outputs have nothing to show a label on
+    monitors
+        .iter()
+        .filter(|monitor| !monitor.disabled)
+        .filter_map(|monitor| {
+            process::Command::new(&exe)
+                .args(["identify-output", &monitor.name])
+                .spawn()
+                .map_err(|e| eprintln!("Failed to identify {}: {e}", monitor.name))
+                .ok()
+        })
+        .collect()
+}
+
+/// Stops overlays that are still showing. Returns whether any were.
+pub fn dismiss_overlays(overlays: &mut Vec<Child>) -> bool {
+    let mut dismissed = false;
+    for mut overlay in overlays.drain(..) {
+        if matches!(overlay.try_wait(), Ok(None)) {
+            dismissed = true;
+            let _ = overlay.kill();
+        }
+        let _ = overlay.wait();
+    }
+    dismissed
+}
+
+/// Runs the label overlay for a single output. Blocks until it is dismissed.
+pub fn run_overlay(monitor_name: &str) -> ExitCode {
+    let monitor = default_backend()
+        .monitors()
+        .ok()
+        .and_then(|monitors| monitors.into_iter().find(|m| m.name == monitor_name));
+
+    let lines = match monitor {
+        Some(monitor) => vec![
+            monitor.name.clone(),
+            monitor.description.clone(),
+            format!(
+                "{}x{} @ {:.2} Hz",
+                monitor.width, monitor.height, monitor.refresh_rate
+            ),
+        ],
+        None => vec![monitor_name.to_string()],
+    };
+
+    let result = IdentifyOverlay::run(Settings {
+        layer_settings: LayerShellSettings {
+            size: Some((640, 240)),
+            exclusive_zone: 0,
+            anchor: Anchor::empty(),
+            layer: Layer::Overlay,
+            keyboard_interactivity: KeyboardInteractivity::OnDemand,
+            start_mode: StartMode::TargetScreen(monitor_name.to_string()),
+            ..Default::default()
+        },
+        flags: lines,
+        ..Default::default()
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Failed to show identify overlay: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[to_layer_message]
+#[derive(Debug, Clone)]
+pub enum OverlayMessage {
+    Dismiss,
+    IcedEvent(Event),
+}
+
+/// Big label with the output's connector name, description and mode
+struct IdentifyOverlay {
+    lines: Vec<String>,
+}
+
+impl Application for IdentifyOverlay {
+    type Message = OverlayMessage;
+    type Flags = Vec<String>;
+    type Theme = Theme;
+    type Executor = iced::executor::Default;
+
+    fn new(lines: Vec<String>) -> (Self, Task<OverlayMessage>) {
+        (Self { lines }, Task::none())
+    }
+
+    fn namespace(&self) -> String {
+        "display-switcher-identify".to_string()
+    }
+
+    fn update(&mut self, message: OverlayMessage) -> Task<OverlayMessage> {
+        match message {
+            OverlayMessage::Dismiss
+            | OverlayMessage::IcedEvent(
+                Event::Keyboard(keyboard::Event::KeyPressed { .. })
+                | Event::Mouse(mouse::Event::ButtonPressed(_)),
+            ) => process::exit(0),
+            _ => Task::none(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, OverlayMessage> {
+        let lines = self.lines.iter().enumerate().map(|(index, line)| {
+            // The connector name is what the pick lists show, so make it stand out
+            let size = if index == 0 { 72 } else { 20 };
+            text(line.as_str())
+                .size(size)
+                .style(ui::title_text_style())
+                .into()
+        });
+
+        container(
+            Column::with_children(lines)
+                .spacing(8)
+                .align_x(alignment::Horizontal::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(alignment::Horizontal::Center)
+        .align_y(alignment::Vertical::Center)
+        .style(ui::main_container_style())
+        .into()
+    }
+
+    fn subscription(&self) -> iced::Subscription<OverlayMessage> {
+        iced::Subscription::batch([
+            event::listen().map(OverlayMessage::IcedEvent),
+            time::every(IDENTIFY_TIMEOUT).map(|_| OverlayMessage::Dismiss),
+        ])
+    }
+
+    fn style(&self, theme: &Self::Theme) -> iced_layershell::Appearance {
+        iced_layershell::Appearance {
+            background_color: iced::Color::TRANSPARENT,
+            text_color: theme.palette().text,
+        }
+    }
+}
@@ -0,0 +1,544 @@
+use crate::config::{ConfigManager, ExtendConfiguration, ExtendLayout, MonitorConfiguration};
+use crate::display::{logical_size, parse_resolution, transform_rotation};
+use hyprland::data::{Monitor, Transforms};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Location of the main Hyprland configuration
+pub fn default_config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hypr")
+        .join("hyprland.conf")
+}
+
+/// Which output a rule applies to
+#[derive(Debug, Clone, PartialEq)]
+enum Output {
+    Name(String),
+    /// `desc:` rule, matched against the start of the monitor description
+    Description(String),
+}
+
+/// A `monitor=` line from the Hyprland configuration
+#[derive(Debug, Clone)]
+struct ParsedRule {
+    output: Output,
+    /// `WxH`, or `auto` for `preferred`, `highres` and the like
+    resolution: String,
+    refresh_rate: Option<f32>,
+    /// `None` for the `auto` placements
+    position: Option<(i32, i32)>,
+    scale: f32,
+    transform: Transforms,
+    /// Connector name of the output this one mirrors
+    mirror: Option<String>,
+    disabled: bool,
+}
+
+/// Reads the monitor rules from a Hyprland configuration file and everything
+/// it sources, then saves them as a profile. Importing the same file again
+/// updates that profile instead of adding another. Returns the profile id.
+pub fn import_profile(
+    path: &Path,
+    config_manager: &mut ConfigManager,
+    connected: &[Monitor],
+) -> Result<String, String> {
+    let mut reader = ConfigReader::default();
+    reader.read_file(path)?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let config = profile_from_rules(&reader.rules, connected, &file_name)?;
+
+    let existing_id = config_manager.find_profile_id(&config.name, &config.monitor_set_key());
+    config_manager
+        .save_extend_configuration(existing_id.as_deref(), config)
+        .map_err(|e| format!("Failed to save imported profile: {e}"))
+}
+
+/// Imports the rules from hyprland.conf the first time the switcher runs, so
+/// existing setups show up right away. When there is nothing to import the
+/// defaults are written instead, so this is only tried once.
+pub fn import_on_first_run(config_manager: &mut ConfigManager, connected: &[Monitor]) {
+    if !config_manager.is_first_run() {
+        return;
+    }
+
+    match import_profile(&default_config_path(), config_manager, connected) {
+        Ok(profile_id) => {
+            eprintln!("Imported monitor rules from hyprland.conf as profile {profile_id}");
+        }
+        Err(e) => {
+            eprintln!("Not importing monitor rules: {e}");
+            if let Err(e) = config_manager.save_to_disk() {
+                eprintln!("Failed to save configuration: {e}");
+            }
+        }
+    }
+}
+
+/// Collects `monitor=` rules while following `source=` includes and
+/// expanding `$variables`
+#[derive(Default)]
+struct ConfigReader {
+    variables: HashMap<String, String>,
+    visited: HashSet<PathBuf>,
+    rules: Vec<ParsedRule>,
+}
+
+impl ConfigReader {
+    fn read_file(&mut self, path: &Path) -> Result<(), String> {
+        let path = fs::canonicalize(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        // Guards against files that source each other
+        if !self.visited.insert(path.clone()) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let directory = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+
+        for line in content.lines() {
+            let line = strip_comment(line);
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim();
+            let value = self.expand_variables(value.trim());
+
+            if let Some(variable) = key.strip_prefix('$') {
+                self.variables.insert(variable.to_string(), value);
+            } else if key == "source" {
+                self.read_source(&value, &directory);
+            } else if key == "monitor"
+                && let Some(rule) = parse_rule(&value)
+            {
+                // A later rule for the same output replaces the earlier one
+                match self.rules.iter_mut().find(|r| r.output == rule.output) {
+                    Some(existing) => *existing = rule,
+                    None => self.rules.push(rule),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Follows a `source=` line. Missing includes are skipped like Hyprland
+    /// does, with a warning.
+    fn read_source(&mut self, value: &str, directory: &Path) {
+        if value.contains(['*', '?', '[']) {
+            eprintln!("Skipping source with a glob pattern: {value}");
+            return;
+        }
+
+        let path = match value.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => directory.join(value),
+        };
+        if let Err(e) = self.read_file(&path) {
+            eprintln!("Skipping source: {e}");
+        }
+    }
+
+    fn expand_variables(&self, value: &str) -> String {
+        if !value.contains('$') {
+            return value.to_string();
+        }
+
+        // Longest names first, so `$mon` does not eat the start of `$monitor`
+        let mut names: Vec<&String> = self.variables.keys().collect();
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+        let mut expanded = value.to_string();
+        for name in names {
+            expanded = expanded.replace(&format!("${name}"), &self.variables[name]);
+        }
+        expanded
+    }
+}
+
+/// Drops a trailing comment. `##` stands for a literal `#`.
+fn strip_comment(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '#' {
+            stripped.push(c);
+        } else if chars.peek() == Some(&'#') {
+            chars.next();
+            stripped.push('#');
+        } else {
+            break;
+        }
+    }
+    stripped
+}
+
+/// Parses the value of a `monitor=` line, e.g.
+/// `DP-1, 2560x1440@144, 1920x0, 1.25, transform, 1`. Returns `None` for
+/// catch-all rules without an output and for rules that do not configure a
+/// mode, such as `addreserved`.
+fn parse_rule(value: &str) -> Option<ParsedRule> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+
+    let output = match fields.first()? {
+        &"" => return None,
+        name => match name.strip_prefix("desc:") {
+            Some(description) => Output::Description(description.trim().to_string()),
+            None => Output::Name(name.to_string()),
+        },
+    };
+
+    let mode = *fields.get(1)?;
+    let mut rule = ParsedRule {
+        output,
+        resolution: "auto".to_string(),
+        refresh_rate: None,
+        position: None,
+        scale: 1.0,
+        transform: Transforms::Normal,
+        mirror: None,
+        disabled: false,
+    };
+
+    match mode {
+        "disable" | "disabled" => {
+            rule.disabled = true;
+            return Some(rule);
+        }
+        "addreserved" => return None,
+        _ => {}
+    }
+
+    let (resolution, refresh_rate) = match mode.split_once('@') {
+        Some((resolution, refresh_rate)) => (resolution, refresh_rate.parse().ok()),
+        None => (mode, None),
+    };
+    if let Some((width, height)) = resolution.split_once('x')
+        && width.parse::<u32>().is_ok()
+        && height.parse::<u32>().is_ok()
+    {
+        rule.resolution = resolution.to_string();
+        rule.refresh_rate = refresh_rate;
+    }
+
+    rule.position = fields.get(2).and_then(|position| {
+        let (x, y) = position.split_once('x')?;
+        Some((x.parse().ok()?, y.parse().ok()?))
+    });
+    rule.scale = fields
+        .get(3)
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(1.0);
+
+    for option in fields.get(4..).unwrap_or_default().chunks(2) {
+        match option {
+            ["transform", transform] => {
+                rule.transform = parse_transform(transform).unwrap_or(Transforms::Normal)
+            }
+            ["mirror", source] => rule.mirror = Some(source.to_string()),
+            _ => {}
+        }
+    }
+
+    Some(rule)
+}
+
+fn parse_transform(value: &str) -> Option<Transforms> {
+    Some(match value.parse::<u8>().ok()? {
+        0 => Transforms::Normal,
+        1 => Transforms::Normal90,
+        2 => Transforms::Normal180,
+        3 => Transforms::Normal270,
+        4 => Transforms::Flipped,
+        5 => Transforms::Flipped90,
+        6 => Transforms::Flipped180,
+        7 => Transforms::Flipped270,
+        _ => return None,
+    })
+}
+
+/// Builds a profile for the connected monitors from the rules Hyprland
+/// applies to them. Every connected monitor needs a rule, otherwise the
+/// profile would never match them; rules for other outputs are left out.
+/// Outputs placed with `auto` go to the right of the ones with explicit
+/// positions.
+fn profile_from_rules(
+    rules: &[ParsedRule],
+    connected: &[Monitor],
+    source_name: &str,
+) -> Result<ExtendConfiguration, String> {
+    let mut monitors = Vec::with_capacity(connected.len());
+    for monitor in connected {
+        let rule = rule_for_monitor(rules, monitor)
+            .ok_or_else(|| format!("No monitor rule for {} in {source_name}", monitor.name))?;
+        monitors.push(MonitorConfiguration {
+            name: monitor.name.clone(),
+            description: monitor.description.clone(),
+            resolution: rule.resolution.clone(),
+            refresh_rate: rule.refresh_rate,
+            scale: rule.scale,
+            rotation: transform_rotation(rule.transform).to_string(),
+            position: rule.position,
+            mirror: rule.mirror.clone(),
+            disabled: rule.disabled,
+        });
+    }
+
+    let is_placed = |m: &MonitorConfiguration| !m.disabled && m.mirror.is_none();
+    let logical_width = |config: &MonitorConfiguration, monitor: &Monitor| {
+        let size = if config.resolution == "auto" {
+            (monitor.width as i32, monitor.height as i32)
+        } else {
+            parse_resolution(&config.resolution)
+        };
+        logical_size(size, &config.rotation, config.scale).0
+    };
+
+    let mut right_edge = monitors
+        .iter()
+        .zip(connected)
+        .filter(|(config, _)| is_placed(config))
+        .filter_map(|(config, monitor)| Some(config.position?.0 + logical_width(config, monitor)))
+        .max()
+        .unwrap_or(0);
+    for (config, monitor) in monitors.iter_mut().zip(connected) {
+        if !is_placed(config) {
+            // Mirrors show their source wherever it is, disabled outputs
+            // take no part in the layout
+            config.position.get_or_insert((0, 0));
+        } else if config.position.is_none() {
+            config.position = Some((right_edge, 0));
+            right_edge += logical_width(config, monitor);
+        }
+    }
+    monitors.sort_by_key(|m| (m.disabled, m.position));
+
+    let primary_monitor = monitors
+        .iter()
+        .filter(|m| is_placed(m))
+        .find(|m| m.position == Some((0, 0)))
+        .or_else(|| monitors.iter().find(|m| is_placed(m)))
+        .map(|m| m.name.clone())
+        .ok_or_else(|| format!("No enabled monitor in {source_name}"))?;
+
+    let mut config = ConfigManager::create_config_from_settings(
+        primary_monitor,
+        monitors,
+        ExtendLayout::Custom,
+        Default::default(),
+        0,
+    );
+    config.name = format!("Imported from {source_name}");
+    Ok(config)
+}
+
+/// The rule Hyprland picks for a monitor: one for its description wins over
+/// one for its connector name
+fn rule_for_monitor<'a>(rules: &'a [ParsedRule], monitor: &Monitor) -> Option<&'a ParsedRule> {
+    rules
+        .iter()
+        .find(|rule| {
+            matches!(&rule.output, Output::Description(description)
+                if monitor.description.starts_with(description.as_str()))
+        })
+        .or_else(|| {
+            rules
+                .iter()
+                .find(|rule| matches!(&rule.output, Output::Name(name) if name == &monitor.name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{DisplayBackend, FakeBackend};
+
+    /// eDP-1 "Laptop panel" at 1920x1200 and DP-3 "External monitor" at
+    /// 2560x1440
+    fn connected() -> Vec<Monitor> {
+        FakeBackend::with_sample_monitors().monitors().unwrap()
+    }
+
+    fn rules(lines: &[&str]) -> Vec<ParsedRule> {
+        lines.iter().filter_map(|line| parse_rule(line)).collect()
+    }
+
+    #[test]
+    fn strip_comment_keeps_escaped_hashes() {
+        assert_eq!(
+            strip_comment("monitor = DP-1, auto # dock"),
+            "monitor = DP-1, auto "
+        );
+        assert_eq!(strip_comment("$color = ##ff0000"), "$color = #ff0000");
+        assert_eq!(strip_comment("# monitor = DP-1, auto"), "");
+        assert_eq!(
+            strip_comment("monitor = DP-1, auto"),
+            "monitor = DP-1, auto"
+        );
+    }
+
+    #[test]
+    fn expand_variables_prefers_longer_names() {
+        let reader = ConfigReader {
+            variables: HashMap::from([
+                ("mon".to_string(), "DP-1".to_string()),
+                ("monitor".to_string(), "eDP-1".to_string()),
+            ]),
+            ..ConfigReader::default()
+        };
+
+        assert_eq!(reader.expand_variables("$monitor, $mon"), "eDP-1, DP-1");
+        assert_eq!(reader.expand_variables("DP-2, auto"), "DP-2, auto");
+        assert_eq!(reader.expand_variables("$unknown"), "$unknown");
+    }
+
+    #[test]
+    fn parse_rule_reads_every_field() {
+        let rule = parse_rule("DP-1, 2560x1440@143.97, 1920x-200, 1.25, transform, 1").unwrap();
+
+        assert_eq!(rule.output, Output::Name("DP-1".to_string()));
+        assert_eq!(rule.resolution, "2560x1440");
+        assert_eq!(rule.refresh_rate, Some(143.97));
+        assert_eq!(rule.position, Some((1920, -200)));
+        assert_eq!(rule.scale, 1.25);
+        assert_eq!(rule.transform, Transforms::Normal90);
+        assert!(!rule.disabled);
+    }
+
+    #[test]
+    fn parse_rule_handles_descriptions_and_auto_values() {
+        let rule = parse_rule("desc:Dell Inc. U2720Q, preferred, auto, 1").unwrap();
+
+        assert_eq!(
+            rule.output,
+            Output::Description("Dell Inc. U2720Q".to_string())
+        );
+        assert_eq!(rule.resolution, "auto");
+        assert_eq!(rule.refresh_rate, None);
+        assert_eq!(rule.position, None);
+    }
+
+    #[test]
+    fn parse_rule_handles_disable_and_mirror() {
+        assert!(parse_rule("eDP-1, disable").unwrap().disabled);
+
+        let rule = parse_rule("HDMI-A-1, 1920x1080, 0x0, 1, mirror, eDP-1").unwrap();
+        assert_eq!(rule.mirror.as_deref(), Some("eDP-1"));
+    }
+
+    #[test]
+    fn parse_rule_skips_rules_without_a_mode() {
+        assert!(parse_rule(", preferred, auto, 1").is_none());
+        assert!(parse_rule("DP-1, addreserved, 10, 0, 0, 0").is_none());
+        assert!(parse_rule("DP-1").is_none());
+    }
+
+    #[test]
+    fn profile_covers_only_the_connected_monitors() {
+        let rules = rules(&[
+            "eDP-1, 1920x1200@60, 0x0, 1",
+            "desc:External, 2560x1440@144, 1920x0, 1",
+            "HDMI-A-1, 1920x1080, auto, 1",
+        ]);
+
+        let config = profile_from_rules(&rules, &connected(), "hyprland.conf").unwrap();
+
+        let monitors: Vec<(&str, Option<(i32, i32)>)> = config
+            .monitors
+            .iter()
+            .map(|m| (m.name.as_str(), m.position))
+            .collect();
+        assert_eq!(
+            monitors,
+            [("eDP-1", Some((0, 0))), ("DP-3", Some((1920, 0)))]
+        );
+        assert_eq!(config.monitors[1].refresh_rate, Some(144.0));
+        assert_eq!(config.primary_monitor, "eDP-1");
+        assert_eq!(config.layout, ExtendLayout::Custom);
+        assert!(config.resolve_for_monitors(&connected()).is_some());
+    }
+
+    #[test]
+    fn auto_positions_go_right_of_the_placed_monitors() {
+        let rules = rules(&["eDP-1, 1920x1200, 0x0, 1.25", "DP-3, preferred, auto, 1"]);
+
+        let config = profile_from_rules(&rules, &connected(), "hyprland.conf").unwrap();
+
+        assert_eq!(config.monitors[1].name, "DP-3");
+        assert_eq!(config.monitors[1].position, Some((1536, 0)));
+    }
+
+    #[test]
+    fn description_rules_win_over_connector_rules() {
+        let rules = rules(&[
+            "DP-3, 1920x1080, 1920x0, 1",
+            "desc:External monitor, 2560x1440, 1920x0, 1",
+            "eDP-1, 1920x1200, 0x0, 1",
+        ]);
+
+        let config = profile_from_rules(&rules, &connected(), "hyprland.conf").unwrap();
+
+        let external = config.monitors.iter().find(|m| m.name == "DP-3").unwrap();
+        assert_eq!(external.resolution, "2560x1440");
+    }
+
+    #[test]
+    fn disabled_rules_keep_the_output_off() {
+        let rules = rules(&["eDP-1, disable", "DP-3, 2560x1440, 0x0, 1"]);
+
+        let config = profile_from_rules(&rules, &connected(), "hyprland.conf").unwrap();
+
+        assert_eq!(config.primary_monitor, "DP-3");
+        assert!(
+            config
+                .monitors
+                .iter()
+                .any(|m| m.name == "eDP-1" && m.disabled)
+        );
+    }
+
+    #[test]
+    fn profile_needs_a_rule_for_every_connected_monitor() {
+        let rules = rules(&["eDP-1, 1920x1200, 0x0, 1", "HDMI-A-1, 1920x1080, 1920x0, 1"]);
+
+        assert!(profile_from_rules(&rules, &connected(), "hyprland.conf").is_err());
+    }
+
+    #[test]
+    fn read_file_follows_sources_and_variables() {
+        let dir =
+            std::env::temp_dir().join(format!("display-switcher-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("hyprland.conf"),
+            "$external = DP-3\nsource = monitors.conf\nmonitor = eDP-1, 1920x1200, 0x0, 1\n",
+        )
+        .unwrap();
+        // Sourcing the main file again must not loop
+        fs::write(
+            dir.join("monitors.conf"),
+            "monitor = $external, 2560x1440, 1920x0, 1 # dock\nsource = hyprland.conf\n",
+        )
+        .unwrap();
+
+        let mut reader = ConfigReader::default();
+        reader.read_file(&dir.join("hyprland.conf")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        let outputs: Vec<&Output> = reader.rules.iter().map(|rule| &rule.output).collect();
+        assert_eq!(
+            outputs,
+            [
+                &Output::Name("DP-3".to_string()),
+                &Output::Name("eDP-1".to_string())
+            ]
+        );
+    }
+}
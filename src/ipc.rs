@@ -0,0 +1,198 @@
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Monitor entry from `j/monitors all`, limited to the fields hyprland-rs
+/// does not expose
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorModes {
+    pub name: String,
+    #[serde(default)]
+    pub available_modes: Vec<String>,
+    /// Name of the mirrored monitor, `none` when not mirroring
+    #[serde(default)]
+    pub mirror_of: String,
+}
+
+/// Path of a Hyprland socket (`.socket.sock` for requests, `.socket2.sock`
+/// for events) of the running instance
+fn socket_path(socket: &str) -> Result<PathBuf, String> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| "HYPRLAND_INSTANCE_SIGNATURE is not set, is Hyprland running?".to_string())?;
+
+    let runtime_path = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr").join(&signature))
+        .filter(|path| path.exists());
+
+    Ok(runtime_path
+        .unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(&signature))
+        .join(socket))
+}
+
+/// Sends a raw request to Hyprland and returns its reply
+pub fn request(command: &str) -> Result<String, String> {
+    let path = socket_path(".socket.sock")?;
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|e| format!("Failed to connect to {}: {e}", path.display()))?;
+
+    stream
+        .write_all(command.as_bytes())
+        .map_err(|e| format!("Failed to send request: {e}"))?;
+
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|e| format!("Failed to read reply: {e}"))?;
+    Ok(reply)
+}
+
+/// Sends a request that Hyprland answers with `ok` on success and an error
+/// message otherwise
+pub fn request_ok(command: &str) -> Result<(), String> {
+    let reply = request(command)?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        error => Err(format!("Hyprland rejected `{command}`: {error}")),
+    }
+}
+
+/// Sends all commands in one `[[BATCH]]` request, which Hyprland runs back to
+/// back without processing anything in between. On failure the error names
+/// the first command that was not acknowledged with `ok`.
+pub fn batch(commands: &[String]) -> Result<(), String> {
+    let reply = request(&format!("[[BATCH]]{}", commands.join(";")))?;
+    check_batch_reply(commands, &reply)
+}
+
+/// Checks the reply to a batch, in which the replies of the commands are
+/// concatenated in command order. Error messages have no terminator, so
+/// everything from the first failure on is reported.
+fn check_batch_reply(commands: &[String], reply: &str) -> Result<(), String> {
+    let mut remaining = reply.trim_start();
+    for (index, command) in commands.iter().enumerate() {
+        match remaining.strip_prefix("ok") {
+            Some(rest) => remaining = rest.trim_start(),
+            None => {
+                let error = match remaining.trim() {
+                    "" => "no reply",
+                    error => error,
+                };
+                return Err(format!(
+                    "Command {} of {} (`{command}`) failed: {error}",
+                    index + 1,
+                    commands.len()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn monitors_with_modes() -> Result<Vec<MonitorModes>, String> {
+    let reply = request("j/monitors all")?;
+    serde_json::from_str(&reply).map_err(|e| format!("Failed to parse monitors: {e}"))
+}
+
+/// Subscription to Hyprland's event socket. Connect before making a change so
+/// the events it triggers are not missed.
+pub struct EventStream {
+    reader: BufReader<UnixStream>,
+}
+
+impl EventStream {
+    pub fn connect() -> Result<Self, String> {
+        let path = socket_path(".socket2.sock")?;
+        let stream = UnixStream::connect(&path)
+            .map_err(|e| format!("Failed to connect to {}: {e}", path.display()))?;
+        Ok(Self {
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// Blocks until the next event line arrives, e.g. `monitoradded>>DP-1`.
+    /// Returns `None` once the socket is closed.
+    pub fn next_event(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end().to_string()),
+        }
+    }
+
+    /// Waits until every expected event line (e.g. `monitoradded>>DP-1`) has
+    /// been received or the timeout expires. Returns the events that did not
+    /// arrive.
+    pub fn wait_for(mut self, mut expected: Vec<String>, timeout: Duration) -> Vec<String> {
+        let deadline = Instant::now() + timeout;
+        let mut line = String::new();
+
+        while !expected.is_empty() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero()
+                || self
+                    .reader
+                    .get_ref()
+                    .set_read_timeout(Some(remaining))
+                    .is_err()
+            {
+                break;
+            }
+
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => expected.retain(|event| event != line.trim_end()),
+            }
+        }
+
+        expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands() -> Vec<String> {
+        vec![
+            "keyword monitor eDP-1,1920x1200,0x0,1".to_string(),
+            "keyword monitor DP-3,disable".to_string(),
+        ]
+    }
+
+    #[test]
+    fn batch_reply_with_every_command_acknowledged() {
+        assert!(check_batch_reply(&commands(), "okok").is_ok());
+        assert!(check_batch_reply(&commands(), "ok\n\nok\n").is_ok());
+    }
+
+    #[test]
+    fn batch_reply_reports_the_whole_error() {
+        let error = check_batch_reply(&commands(), "okinvalid token for monitor").unwrap_err();
+
+        assert!(error.contains("Command 2 of 2"), "{error}");
+        assert!(
+            error.ends_with("failed: invalid token for monitor"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn batch_reply_reports_the_first_failed_command() {
+        let error = check_batch_reply(&commands(), "unknown monitor\n\nok").unwrap_err();
+
+        assert!(error.contains("Command 1 of 2"), "{error}");
+        assert!(error.contains("unknown monitor"), "{error}");
+    }
+
+    #[test]
+    fn batch_reply_missing_acknowledgements_fail() {
+        let error = check_batch_reply(&commands(), "ok").unwrap_err();
+
+        assert!(error.ends_with("failed: no reply"), "{error}");
+    }
+}
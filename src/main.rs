@@ -1,23 +1,61 @@
 mod app;
+mod backend;
+mod backup;
+mod cli;
 mod config;
+mod daemon;
 mod display;
+mod fsutil;
+mod identify;
+mod import;
+mod ipc;
+mod migrate;
+mod monitor_rule;
+mod monitors_conf;
+mod notify;
 mod state;
 mod ui;
 
-use app::DisplaySwitcher;
+use app::{DisplaySwitcher, LaunchOptions};
 use iced_layershell::reexport::{Anchor, KeyboardInteractivity, Layer};
-use iced_layershell::settings::{LayerShellSettings, Settings};
+use iced_layershell::settings::{LayerShellSettings, Settings, StartMode};
+use std::process::ExitCode;
 
-fn main() -> Result<(), iced_layershell::Error> {
-    DisplaySwitcher::run(Settings {
+fn main() -> ExitCode {
+    match cli::parse_args(std::env::args().skip(1)) {
+        Ok(cli::Command::Gui) => run_gui(),
+        Ok(cli::Command::IdentifyOutput(name)) => identify::run_overlay(&name),
+        Ok(command) => cli::run(command),
+        Err(e) => cli::usage_error(&e),
+    }
+}
+
+fn run_gui() -> ExitCode {
+    let options = LaunchOptions::detect();
+    let start_mode = match &options.output {
+        Some(output) => StartMode::TargetScreen(output.clone()),
+        None => StartMode::default(),
+    };
+
+    let result = DisplaySwitcher::run(Settings {
         layer_settings: LayerShellSettings {
             size: Some((500, 800)),
             exclusive_zone: 0,
             anchor: Anchor::empty(),
             layer: Layer::Overlay,
             keyboard_interactivity: KeyboardInteractivity::Exclusive,
+            start_mode,
             ..Default::default()
         },
+        flags: options,
         ..Default::default()
-    })
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Failed to run display switcher: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
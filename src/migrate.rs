@@ -0,0 +1,253 @@
+use crate::config::DisplayConfig;
+use serde_json::{Map, Value, json};
+
+/// Format version written to `DisplayConfig::version`. Bump it and append a
+/// step to `MIGRATIONS` whenever saved configurations stop deserializing.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Rewrites a configuration in place from one version to the next
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Upgrade steps, indexed by the version they upgrade from
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Result of reading a configuration file
+pub struct LoadedConfig {
+    pub config: DisplayConfig,
+    /// Version the file had, when it was older or newer than this build
+    pub original_version: Option<u32>,
+}
+
+/// Parses a configuration file of any known version and upgrades it to the
+/// current format
+pub fn parse_config(content: &str) -> Result<LoadedConfig, String> {
+    let mut value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let version = detect_version(&value);
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut value).map_err(|e| format!("Migrating from version {from} failed: {e}"))?;
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or("Configuration is not a JSON object")?;
+    // Keep a newer version so it is not mistaken for this one, fields this
+    // build does not know are dropped on the next save either way
+    object.insert("version".to_string(), json!(version.max(CONFIG_VERSION)));
+
+    let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(LoadedConfig {
+        config,
+        original_version: (version != CONFIG_VERSION).then_some(version),
+    })
+}
+
+/// Version of a configuration file. Files from before the `version` field
+/// are version 1, unless they still use the two-monitor format of version 0.
+fn detect_version(value: &Value) -> u32 {
+    if let Some(version) = value.get("version").and_then(Value::as_u64) {
+        return version as u32;
+    }
+
+    let legacy = value
+        .get("extend_configurations")
+        .and_then(Value::as_object)
+        .is_some_and(|configs| {
+            configs
+                .values()
+                .any(|config| config.get("secondary_monitor").is_some())
+        });
+    if legacy { 0 } else { 1 }
+}
+
+/// Version 0 stored exactly two monitors per configuration as
+/// `primary_*` and `secondary_*` fields. Version 1 keeps a list of monitors
+/// in layout order.
+fn migrate_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    let Some(configs) = value
+        .get_mut("extend_configurations")
+        .and_then(Value::as_object_mut)
+    else {
+        return Ok(());
+    };
+
+    for (id, config) in configs.iter_mut() {
+        let config = config
+            .as_object_mut()
+            .ok_or_else(|| format!("Configuration {id} is not a JSON object"))?;
+        if !config.contains_key("secondary_monitor") {
+            continue;
+        }
+
+        let monitors: Vec<Value> = ["primary", "secondary"]
+            .iter()
+            .map(|role| legacy_monitor(config, role))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Configuration {id}: {e}"))?;
+        config.remove("secondary_monitor");
+        config.insert("monitors".to_string(), Value::Array(monitors));
+    }
+
+    Ok(())
+}
+
+/// Builds a monitor entry from the `<role>_monitor`, `<role>_resolution` and
+/// `<role>_rotation` fields of a version 0 configuration. `primary_monitor`
+/// is still used by version 1, so it stays in place.
+fn legacy_monitor(config: &mut Map<String, Value>, role: &str) -> Result<Value, String> {
+    let name = config
+        .get(&format!("{role}_monitor"))
+        .cloned()
+        .ok_or_else(|| format!("{role}_monitor is missing"))?;
+    let resolution = config
+        .remove(&format!("{role}_resolution"))
+        .unwrap_or_else(|| json!("auto"));
+    let rotation = config
+        .remove(&format!("{role}_rotation"))
+        .unwrap_or_else(|| json!("normal"));
+
+    Ok(json!({
+        "name": name,
+        "resolution": resolution,
+        "rotation": rotation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExtendLayout;
+
+    const V0_CONFIG: &str = r#"{
+        "extend_configurations": {
+            "eDP-1+DP-3": {
+                "name": "Desk",
+                "primary_monitor": "eDP-1",
+                "secondary_monitor": "DP-3",
+                "primary_resolution": "1920x1200",
+                "secondary_resolution": "2560x1440",
+                "primary_rotation": "normal",
+                "secondary_rotation": "left",
+                "layout": "RightToLeft",
+                "created_at": "2024-05-01T10:00:00Z",
+                "last_used": "2024-06-01T10:00:00Z"
+            }
+        },
+        "last_used_extend_config": "eDP-1+DP-3"
+    }"#;
+
+    #[test]
+    fn two_monitor_configurations_are_migrated() {
+        let loaded = parse_config(V0_CONFIG).unwrap();
+
+        assert_eq!(loaded.original_version, Some(0));
+        assert_eq!(loaded.config.version, CONFIG_VERSION);
+        assert_eq!(
+            loaded.config.last_used_extend_config.as_deref(),
+            Some("eDP-1+DP-3")
+        );
+
+        let config = &loaded.config.extend_configurations["eDP-1+DP-3"];
+        assert_eq!(config.name, "Desk");
+        assert_eq!(config.primary_monitor, "eDP-1");
+        assert_eq!(config.layout, ExtendLayout::RightToLeft);
+        assert_eq!(config.last_used.to_rfc3339(), "2024-06-01T10:00:00+00:00");
+
+        let monitors: Vec<(&str, &str, &str)> = config
+            .monitors
+            .iter()
+            .map(|m| (m.name.as_str(), m.resolution.as_str(), m.rotation.as_str()))
+            .collect();
+        assert_eq!(
+            monitors,
+            [
+                ("eDP-1", "1920x1200", "normal"),
+                ("DP-3", "2560x1440", "left")
+            ]
+        );
+        assert!(config.monitors.iter().all(|m| m.description.is_empty()));
+    }
+
+    #[test]
+    fn migration_defaults_missing_resolution_and_rotation() {
+        let content = r#"{
+            "extend_configurations": {
+                "a": {
+                    "name": "Desk",
+                    "primary_monitor": "eDP-1",
+                    "secondary_monitor": "DP-3",
+                    "layout": "LeftToRight",
+                    "created_at": "2024-05-01T10:00:00Z",
+                    "last_used": "2024-05-01T10:00:00Z"
+                }
+            },
+            "last_used_extend_config": null
+        }"#;
+
+        let loaded = parse_config(content).unwrap();
+
+        let monitor = &loaded.config.extend_configurations["a"].monitors[1];
+        assert_eq!(monitor.resolution, "auto");
+        assert_eq!(monitor.rotation, "normal");
+    }
+
+    #[test]
+    fn unversioned_list_configurations_are_version_1() {
+        let content = r#"{
+            "extend_configurations": {
+                "a": {
+                    "name": "Desk",
+                    "primary_monitor": "DP-3",
+                    "monitors": [
+                        {"name": "DP-3", "resolution": "auto", "rotation": "normal"}
+                    ],
+                    "layout": "LeftToRight",
+                    "created_at": "2024-05-01T10:00:00Z",
+                    "last_used": "2024-05-01T10:00:00Z"
+                }
+            },
+            "last_used_extend_config": null
+        }"#;
+
+        let loaded = parse_config(content).unwrap();
+
+        assert_eq!(loaded.original_version, None);
+        assert_eq!(loaded.config.extend_configurations["a"].monitors.len(), 1);
+    }
+
+    #[test]
+    fn newer_versions_are_kept_and_reported() {
+        let content = r#"{
+            "version": 7,
+            "extend_configurations": {},
+            "last_used_extend_config": null,
+            "field_from_the_future": true
+        }"#;
+
+        let loaded = parse_config(content).unwrap();
+
+        assert_eq!(loaded.original_version, Some(7));
+        assert_eq!(loaded.config.version, 7);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(parse_config("{\"extend_configurations\": ").is_err());
+        assert!(parse_config("[]").is_err());
+        assert!(parse_config(r#"{"version": 1, "extend_configurations": 3}"#).is_err());
+    }
+
+    #[test]
+    fn legacy_configurations_without_a_primary_are_rejected() {
+        let content = r#"{
+            "extend_configurations": {
+                "a": {"name": "Desk", "secondary_monitor": "DP-3"}
+            },
+            "last_used_extend_config": null
+        }"#;
+
+        let error = parse_config(content).err().unwrap();
+
+        assert!(error.contains("primary_monitor is missing"), "{error}");
+    }
+}
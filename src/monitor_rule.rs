@@ -0,0 +1,240 @@
+use hyprland::data::{Monitor, Transforms};
+use std::fmt;
+
+/// Resolution part of a monitor rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorMode {
+    /// Let Hyprland pick the monitor's preferred mode
+    Preferred,
+    Resolution {
+        width: i32,
+        height: i32,
+        refresh_rate: Option<f32>,
+    },
+}
+
+impl MonitorMode {
+    pub fn resolution(width: i32, height: i32) -> Self {
+        MonitorMode::Resolution {
+            width,
+            height,
+            refresh_rate: None,
+        }
+    }
+}
+
+impl fmt::Display for MonitorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorMode::Preferred => write!(f, "preferred"),
+            MonitorMode::Resolution {
+                width,
+                height,
+                refresh_rate: None,
+            } => write!(f, "{width}x{height}"),
+            MonitorMode::Resolution {
+                width,
+                height,
+                refresh_rate: Some(refresh_rate),
+            } => write!(f, "{width}x{height}@{refresh_rate:.2}"),
+        }
+    }
+}
+
+/// Fractional scales offered in the settings, in 1/120 steps as used by the
+/// fractional-scale protocol
+const SCALE_STEPS: [u32; 12] = [120, 150, 160, 180, 192, 200, 210, 240, 270, 300, 320, 360];
+
+/// Scales Hyprland accepts for the resolution without adjusting them, i.e.
+/// those that divide it into a whole-pixel logical size
+pub fn valid_scales(width: i32, height: i32) -> Vec<f32> {
+    SCALE_STEPS
+        .iter()
+        .filter(|&&step| {
+            (width as u32 * 120).is_multiple_of(step) && (height as u32 * 120).is_multiple_of(step)
+        })
+        .map(|&step| step as f32 / 120.0)
+        .collect()
+}
+
+/// Formats a scale with as few decimals as needed, e.g. `1`, `1.25`,
+/// `1.333333`
+pub fn format_scale(scale: f32) -> String {
+    let formatted = format!("{scale:.6}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// A single Hyprland monitor rule. `Display` renders it in `monitor=` syntax,
+/// without the `monitor=` prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorRule {
+    pub name: String,
+    pub mode: MonitorMode,
+    pub position: (i32, i32),
+    pub scale: f32,
+    pub transform: Transforms,
+    /// Connector name of the monitor to mirror
+    pub mirror: Option<String>,
+    pub bitdepth: Option<u8>,
+    /// 0 = off, 1 = on, 2 = fullscreen only
+    pub vrr: Option<u8>,
+    pub disabled: bool,
+}
+
+impl MonitorRule {
+    pub fn new(name: impl Into<String>, mode: MonitorMode, position: (i32, i32)) -> Self {
+        Self {
+            name: name.into(),
+            mode,
+            position,
+            scale: 1.0,
+            transform: Transforms::Normal,
+            mirror: None,
+            bitdepth: None,
+            vrr: None,
+            disabled: false,
+        }
+    }
+
+    pub fn disable(name: impl Into<String>) -> Self {
+        Self {
+            disabled: true,
+            ..Self::new(name, MonitorMode::Preferred, (0, 0))
+        }
+    }
+
+    /// Rule that puts the monitor back exactly as Hyprland reported it,
+    /// including leaving it off when it was disabled
+    pub fn from_monitor(monitor: &Monitor) -> Self {
+        if monitor.disabled {
+            return Self::disable(&monitor.name);
+        }
+
+        Self {
+            scale: monitor.scale,
+            transform: monitor.transform,
+            ..Self::new(
+                &monitor.name,
+                MonitorMode::Resolution {
+                    width: monitor.width as i32,
+                    height: monitor.height as i32,
+                    refresh_rate: Some(monitor.refresh_rate),
+                },
+                (monitor.x, monitor.y),
+            )
+        }
+    }
+}
+
+impl fmt::Display for MonitorRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.disabled {
+            return write!(f, "{},disable", self.name);
+        }
+
+        let (x, y) = self.position;
+        write!(
+            f,
+            "{},{},{x}x{y},{}",
+            self.name,
+            self.mode,
+            format_scale(self.scale)
+        )?;
+
+        if self.transform != Transforms::Normal {
+            write!(f, ",transform,{}", self.transform as u8)?;
+        }
+        if let Some(mirror) = &self.mirror {
+            write!(f, ",mirror,{mirror}")?;
+        }
+        if let Some(bitdepth) = self.bitdepth {
+            write!(f, ",bitdepth,{bitdepth}")?;
+        }
+        if let Some(vrr) = self.vrr {
+            write!(f, ",vrr,{vrr}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_rules_only_name_the_monitor() {
+        assert_eq!(MonitorRule::disable("DP-3").to_string(), "DP-3,disable");
+    }
+
+    #[test]
+    fn rules_include_mode_position_and_scale() {
+        let rule = MonitorRule::new("eDP-1", MonitorMode::resolution(1920, 1200), (0, 0));
+        assert_eq!(rule.to_string(), "eDP-1,1920x1200,0x0,1");
+
+        let rule = MonitorRule::new("DP-3", MonitorMode::Preferred, (1920, -200));
+        assert_eq!(rule.to_string(), "DP-3,preferred,1920x-200,1");
+    }
+
+    #[test]
+    fn refresh_rates_get_two_decimals() {
+        let mode = MonitorMode::Resolution {
+            width: 2560,
+            height: 1440,
+            refresh_rate: Some(143.99),
+        };
+        assert_eq!(
+            MonitorRule::new("DP-3", mode, (1920, 0)).to_string(),
+            "DP-3,2560x1440@143.99,1920x0,1"
+        );
+
+        let mode = MonitorMode::Resolution {
+            width: 1920,
+            height: 1080,
+            refresh_rate: Some(60.0),
+        };
+        assert_eq!(mode.to_string(), "1920x1080@60.00");
+    }
+
+    #[test]
+    fn transforms_are_written_by_number() {
+        let rule = MonitorRule {
+            transform: Transforms::Normal90,
+            ..MonitorRule::new("DP-3", MonitorMode::resolution(2560, 1440), (0, 0))
+        };
+        assert_eq!(rule.to_string(), "DP-3,2560x1440,0x0,1,transform,1");
+
+        let rule = MonitorRule {
+            transform: Transforms::Flipped270,
+            ..rule
+        };
+        assert_eq!(rule.to_string(), "DP-3,2560x1440,0x0,1,transform,7");
+    }
+
+    #[test]
+    fn mirror_rules_name_the_source() {
+        let rule = MonitorRule {
+            mirror: Some("eDP-1".to_string()),
+            ..MonitorRule::new("DP-3", MonitorMode::resolution(1920, 1200), (0, 0))
+        };
+        assert_eq!(rule.to_string(), "DP-3,1920x1200,0x0,1,mirror,eDP-1");
+    }
+
+    #[test]
+    fn fractional_scales_keep_only_needed_decimals() {
+        assert_eq!(format_scale(1.0), "1");
+        assert_eq!(format_scale(2.0), "2");
+        assert_eq!(format_scale(1.5), "1.5");
+        assert_eq!(format_scale(1.25), "1.25");
+        assert_eq!(format_scale(160.0 / 120.0), "1.333333");
+
+        let rule = MonitorRule {
+            scale: 1.6,
+            ..MonitorRule::new("eDP-1", MonitorMode::resolution(2560, 1600), (0, 0))
+        };
+        assert_eq!(rule.to_string(), "eDP-1,2560x1600,0x0,1.6");
+    }
+}
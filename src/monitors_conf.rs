@@ -0,0 +1,36 @@
+use crate::fsutil::write_atomic;
+use crate::monitor_rule::MonitorRule;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "\
+# Written by hyprland-display-switcher whenever a display mode is applied.
+# Changes made here are overwritten. Source it from hyprland.conf:
+#   source = ~/.config/hypr/monitors.conf
+";
+
+/// Default location of the persisted layout
+pub fn default_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hypr")
+        .join("monitors.conf")
+}
+
+/// Writes the rules as `monitor=` lines, replacing the previous layout.
+/// An empty slice leaves only the header, so Hyprland falls back to the rules
+/// in its own configuration.
+pub fn write(path: &Path, rules: &[MonitorRule]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+
+    let mut content = HEADER.to_string();
+    for rule in rules {
+        content.push_str(&format!("monitor={rule}\n"));
+    }
+
+    write_atomic(path, content.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
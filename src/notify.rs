@@ -0,0 +1,97 @@
+use crate::backend::DisplayBackend;
+use crate::config::ConfigManager;
+use crate::state::DisplayMode;
+use hyprland::data::Monitor;
+use std::collections::HashMap;
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+const APP_NAME: &str = "Hyprland Display Switcher";
+const APP_ICON: &str = "video-display";
+/// How long a notification stays up, in milliseconds
+const EXPIRE_TIMEOUT_MS: i32 = 5000;
+
+// Values of the `urgency` hint
+const URGENCY_NORMAL: u8 = 1;
+const URGENCY_CRITICAL: u8 = 2;
+
+/// Notification title for a mode that was applied
+pub fn mode_title(mode: &DisplayMode) -> &'static str {
+    match mode {
+        DisplayMode::Mirror => "Mirrored",
+        DisplayMode::Extend => "Extended",
+        DisplayMode::MainScreenOnly => "Primary display only",
+        DisplayMode::SecondScreenOnly => "Secondary display only",
+    }
+}
+
+/// Sends a desktop notification with the outcome of a mode change, unless
+/// notifications are disabled in the configuration. On success the body
+/// lists the resulting layout, on failure the error.
+pub fn notify_result(
+    config_manager: &ConfigManager,
+    backend: &dyn DisplayBackend,
+    title: &str,
+    result: &Result<(), String>,
+) {
+    if !config_manager.notifications_enabled() {
+        return;
+    }
+
+    let (summary, body, urgency) = match result {
+        Ok(()) => (
+            title.to_string(),
+            backend
+                .monitors()
+                .map(|monitors| describe_layout(&monitors))
+                .unwrap_or_default(),
+            URGENCY_NORMAL,
+        ),
+        Err(e) => (format!("{title} failed"), e.clone(), URGENCY_CRITICAL),
+    };
+
+    if let Err(e) = send(&summary, &body, urgency) {
+        eprintln!("Failed to send notification: {e}");
+    }
+}
+
+/// Enabled monitors from left to right, e.g.
+/// `eDP-1 1920x1080 · DP-3 2560x1440`
+fn describe_layout(monitors: &[Monitor]) -> String {
+    let mut enabled: Vec<&Monitor> = monitors.iter().filter(|m| !m.disabled).collect();
+    enabled.sort_by_key(|m| (m.x, m.y));
+
+    enabled
+        .iter()
+        .map(|m| format!("{} {}x{}", m.name, m.width, m.height))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Calls `Notify` on the session bus's `org.freedesktop.Notifications`
+fn send(summary: &str, body: &str, urgency: u8) -> Result<(), String> {
+    let connection =
+        Connection::session().map_err(|e| format!("Failed to connect to the session bus: {e}"))?;
+
+    let hints = HashMap::from([("urgency", Value::from(urgency))]);
+    connection
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                APP_NAME,
+                0u32,
+                APP_ICON,
+                summary,
+                body,
+                Vec::<&str>::new(),
+                hints,
+                EXPIRE_TIMEOUT_MS,
+            ),
+        )
+        .map_err(|e| format!("Notify call failed: {e}"))?;
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
-use crate::config::ExtendLayout;
+use crate::backend::AvailableMode;
+use crate::config::{ExtendAlignment, ExtendConfiguration, ExtendLayout};
+use crate::display::{calculate_positions, logical_size, parse_resolution};
+use crate::monitor_rule::{format_scale, valid_scales};
 use hyprland::data::Monitor;
 use iced::Event;
 use iced_layershell::to_layer_message;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub enum State {
@@ -15,29 +19,91 @@ pub enum State {
         settings: MonitorSettingsData,
         selected_field: usize,
     },
+    Profiles {
+        monitors: Vec<Monitor>,
+        selected_index: usize,
+        /// Profile id and draft name while a profile is being renamed
+        renaming: Option<(String, String)>,
+    },
+    /// Display change running in the background
+    Applying {
+        previous_monitors: Vec<Monitor>,
+        previous_mirrors: Vec<(String, String)>,
+        pending_profile: Option<PendingProfile>,
+        /// Message that started the change and the state it came from
+        retry: Box<Message>,
+        previous: Box<State>,
+        /// Set when the switcher's own output went away during the change
+        output_removed: bool,
+    },
+    ConfirmChanges {
+        previous_monitors: Vec<Monitor>,
+        /// Mirroring monitor and the monitor it mirrored before the change
+        previous_mirrors: Vec<(String, String)>,
+        /// Profile to save once the change is kept
+        pending_profile: Option<PendingProfile>,
+        remaining_seconds: u64,
+    },
     Error {
         message: String,
+        /// Set when the error came from an action that can be retried
+        failed_action: Option<Box<FailedAction>>,
     },
 }
 
+/// Action that failed, kept so it can be retried from the error screen
+#[derive(Debug, Clone)]
+pub struct FailedAction {
+    /// Message that started the action
+    pub retry: Message,
+    /// State the action was started from, restored on retry or back
+    pub previous: State,
+}
+
 #[to_layer_message]
 #[derive(Debug, Clone)]
 pub enum Message {
     LoadMonitors,
     MonitorsLoaded(Result<Vec<Monitor>, String>),
+    /// Monitors re-read after a hotplug while the mode list is shown
+    MonitorsRefreshed(Result<Vec<Monitor>, String>),
     SetMode(DisplayMode),
     OpenExtendSettings,
-    UpdatePrimaryResolution(String),
-    UpdatePrimaryRotation(String),
-    UpdateSecondaryResolution(String),
-    UpdateSecondaryRotation(String),
+    UpdateMonitorResolution(usize, String),
+    UpdateMonitorRefreshRate(usize, String),
+    UpdateMonitorScale(usize, String),
+    UpdateMonitorRotation(usize, String),
+    MoveMonitorUp(usize),
+    MoveMonitorDown(usize),
+    /// Monitor dragged in the layout editor to a logical position
+    MoveMonitorTo(usize, i32, i32),
     UpdateLayout(ExtendLayout),
+    UpdateAlignment(ExtendAlignment),
+    UpdateAlignmentOffset(String),
     UpdatePrimaryMonitor(String),
+    UpdateProfileName(String),
     ApplyExtendSettings,
+    OpenProfiles,
+    SaveCurrentLayout,
+    IdentifyMonitors,
+    ApplyProfile(String),
+    StartRenameProfile(String),
+    UpdateRenameDraft(String),
+    SubmitRenameProfile,
+    DuplicateProfile(String),
+    DeleteProfile(String),
+    SetDefaultProfile(String),
     BackToMain,
     Cancel,
     ResetToDefaults,
+    ChangesApplied(Result<(), String>),
+    KeepChanges,
+    RevertChanges,
+    RevertCountdownTick,
+    RetryFailedAction,
+    DismissError,
     IcedEvent(Event),
+    MonitorEvent(MonitorEvent),
     // Navigation messages
     NavigateUp,
     NavigateDown,
@@ -46,38 +112,154 @@ pub enum Message {
     SelectCurrent,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DisplayMode {
     Mirror,
     Extend,
+    #[serde(rename = "primary-only")]
     MainScreenOnly,
+    #[serde(rename = "secondary-only")]
     SecondScreenOnly,
 }
 
+impl std::str::FromStr for DisplayMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mirror" => Ok(DisplayMode::Mirror),
+            "extend" => Ok(DisplayMode::Extend),
+            "primary-only" => Ok(DisplayMode::MainScreenOnly),
+            "secondary-only" => Ok(DisplayMode::SecondScreenOnly),
+            other => Err(format!("Unknown display mode: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingProfile {
+    /// Existing profile to update, or `None` to create a new one
+    pub id: Option<String>,
+    pub config: ExtendConfiguration,
+}
+
+/// Pending confirmation handed to a switcher restarted on another output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmResume {
+    pub previous_monitors: Vec<Monitor>,
+    pub previous_mirrors: Vec<(String, String)>,
+    pub pending_profile: Option<PendingProfile>,
+    pub remaining_seconds: u64,
+}
+
+/// Output hotplug event from Hyprland's event socket
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    Added,
+    Removed(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct MonitorSettingsData {
+    /// Profile being edited, or `None` for a new profile
+    pub profile_id: Option<String>,
+    pub profile_name: String,
     pub primary_monitor: String,
-    pub primary_resolution: String,
-    pub primary_rotation: String,
-    pub secondary_resolution: String,
-    pub secondary_rotation: String,
+    /// Monitors in the order they are laid out
+    pub monitors: Vec<MonitorSettingsEntry>,
     pub layout: ExtendLayout,
-    pub primary_available_resolutions: Vec<String>,
-    pub secondary_available_resolutions: Vec<String>,
+    pub alignment: ExtendAlignment,
+    /// Alignment offset in logical pixels, as typed
+    pub alignment_offset: String,
     pub available_monitors: Vec<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct MonitorSettingsEntry {
+    pub name: String,
+    pub resolution: String,
+    /// Refresh rate such as `144.00`, or `auto` for Hyprland's default
+    pub refresh_rate: String,
+    pub scale: String,
+    pub rotation: String,
+    pub available_resolutions: Vec<String>,
+    pub available_modes: Vec<AvailableMode>,
+    /// Size the monitor currently runs at, used for the `auto` resolution
+    pub current_size: (i32, i32),
+    /// Logical position, used by custom layouts
+    pub position: (i32, i32),
+    /// Monitor this one mirrors, kept from the profile as it is not editable
+    pub mirror: Option<String>,
+}
+
+impl MonitorSettingsData {
+    /// Logical positions the monitors end up at with the current settings
+    pub fn positions(&self) -> Vec<(i32, i32)> {
+        if self.layout == ExtendLayout::Custom {
+            return self.monitors.iter().map(|m| m.position).collect();
+        }
+
+        let sizes: Vec<(i32, i32)> = self.monitors.iter().map(|m| m.logical_size()).collect();
+        calculate_positions(
+            &self.layout,
+            self.alignment,
+            self.alignment_offset.parse().unwrap_or(0),
+            &sizes,
+        )
+    }
+}
+
+impl MonitorSettingsEntry {
+    /// Refresh rates offered for the selected resolution
+    pub fn available_refresh_rates(&self) -> Vec<String> {
+        let mut refresh_rates = vec!["auto".to_string()];
+        for mode in &self.available_modes {
+            let refresh_rate = format!("{:.2}", mode.refresh_rate);
+            if mode.resolution() == self.resolution && !refresh_rates.contains(&refresh_rate) {
+                refresh_rates.push(refresh_rate);
+            }
+        }
+        refresh_rates
+    }
+
+    /// Size of the selected mode in physical pixels
+    pub fn mode_size(&self) -> (i32, i32) {
+        if self.resolution == "auto" {
+            self.current_size
+        } else {
+            parse_resolution(&self.resolution)
+        }
+    }
+
+    pub fn logical_size(&self) -> (i32, i32) {
+        logical_size(
+            self.mode_size(),
+            &self.rotation,
+            self.scale.parse().unwrap_or(1.0),
+        )
+    }
+
+    /// Scales that divide the selected resolution into whole pixels
+    pub fn available_scales(&self) -> Vec<String> {
+        let (width, height) = self.mode_size();
+        valid_scales(width, height)
+            .into_iter()
+            .map(format_scale)
+            .collect()
+    }
+}
+
 impl Default for MonitorSettingsData {
     fn default() -> Self {
         Self {
+            profile_id: None,
+            profile_name: "".to_string(),
             primary_monitor: "".to_string(),
-            primary_resolution: "auto".to_string(),
-            primary_rotation: "normal".to_string(),
-            secondary_resolution: "1920x1080".to_string(),
-            secondary_rotation: "normal".to_string(),
+            monitors: vec![],
             layout: ExtendLayout::LeftToRight,
-            primary_available_resolutions: vec!["auto".to_string()],
-            secondary_available_resolutions: vec!["1920x1080".to_string()],
+            alignment: ExtendAlignment::Start,
+            alignment_offset: "0".to_string(),
             available_monitors: vec![],
         }
     }
@@ -0,0 +1,290 @@
+use crate::state::Message;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, Text, event};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector, alignment, mouse};
+
+/// Space kept free around the monitors, in canvas pixels
+const PADDING: f32 = 16.0;
+/// How close an edge has to be to another monitor's edge to snap to it, in
+/// canvas pixels
+const SNAP_DISTANCE: f32 = 12.0;
+
+/// A monitor as drawn in the layout editor. Positions and sizes are in
+/// logical pixels.
+#[derive(Debug, Clone)]
+pub struct EditorMonitor {
+    pub name: String,
+    pub resolution: String,
+    pub position: (i32, i32),
+    pub size: (i32, i32),
+    pub primary: bool,
+}
+
+/// Canvas program that draws the monitors to scale and lets them be dragged
+/// into place. Every move is reported as `Message::MoveMonitorTo` with the
+/// snapped position.
+pub struct LayoutEditor {
+    pub monitors: Vec<EditorMonitor>,
+}
+
+#[derive(Default)]
+pub struct EditorState {
+    drag: Option<Drag>,
+}
+
+struct Drag {
+    index: usize,
+    /// Cursor offset from the monitor's top-left corner, in logical pixels
+    grab: Vector,
+    /// Mapping frozen at the start of the drag so the canvas does not rescale
+    /// under the cursor
+    view: View,
+}
+
+/// Maps logical monitor coordinates onto the canvas
+#[derive(Debug, Clone, Copy)]
+struct View {
+    scale: f32,
+    origin: Point,
+    offset: Vector,
+}
+
+impl View {
+    fn fit(monitors: &[EditorMonitor], bounds: Size) -> Self {
+        let min_x = monitors.iter().map(|m| m.position.0).min().unwrap_or(0);
+        let min_y = monitors.iter().map(|m| m.position.1).min().unwrap_or(0);
+        let max_x = monitors
+            .iter()
+            .map(|m| m.position.0 + m.size.0)
+            .max()
+            .unwrap_or(1);
+        let max_y = monitors
+            .iter()
+            .map(|m| m.position.1 + m.size.1)
+            .max()
+            .unwrap_or(1);
+
+        let width = (max_x - min_x).max(1) as f32;
+        let height = (max_y - min_y).max(1) as f32;
+        let scale = ((bounds.width - 2.0 * PADDING) / width)
+            .min((bounds.height - 2.0 * PADDING) / height)
+            .max(f32::EPSILON);
+
+        Self {
+            scale,
+            origin: Point::new(min_x as f32, min_y as f32),
+            offset: Vector::new(
+                (bounds.width - width * scale) / 2.0,
+                (bounds.height - height * scale) / 2.0,
+            ),
+        }
+    }
+
+    fn to_canvas(self, (x, y): (i32, i32)) -> Point {
+        Point::new(
+            (x as f32 - self.origin.x) * self.scale + self.offset.x,
+            (y as f32 - self.origin.y) * self.scale + self.offset.y,
+        )
+    }
+
+    fn to_logical(self, point: Point) -> Point {
+        Point::new(
+            (point.x - self.offset.x) / self.scale + self.origin.x,
+            (point.y - self.offset.y) / self.scale + self.origin.y,
+        )
+    }
+
+    fn rectangle(self, monitor: &EditorMonitor) -> Rectangle {
+        Rectangle::new(
+            self.to_canvas(monitor.position),
+            Size::new(
+                monitor.size.0 as f32 * self.scale,
+                monitor.size.1 as f32 * self.scale,
+            ),
+        )
+    }
+}
+
+impl LayoutEditor {
+    /// Moves the edges of the dragged monitor onto nearby edges of the others
+    fn snap(&self, index: usize, (x, y): (i32, i32), scale: f32) -> (i32, i32) {
+        let (width, height) = self.monitors[index].size;
+        let threshold = (SNAP_DISTANCE / scale).round() as i32;
+        let others = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, monitor)| monitor);
+
+        let mut best_x: Option<i32> = None;
+        let mut best_y: Option<i32> = None;
+        for other in others {
+            let (ox, oy) = other.position;
+            let (ow, oh) = other.size;
+
+            // Candidate positions that line up an edge of the dragged monitor
+            // with an edge of the other one
+            for candidate in [ox + ow, ox - width, ox, ox + ow - width] {
+                if (candidate - x).abs() <= threshold
+                    && best_x.is_none_or(|best| (candidate - x).abs() < (best - x).abs())
+                {
+                    best_x = Some(candidate);
+                }
+            }
+            for candidate in [oy + oh, oy - height, oy, oy + oh - height] {
+                if (candidate - y).abs() <= threshold
+                    && best_y.is_none_or(|best| (candidate - y).abs() < (best - y).abs())
+                {
+                    best_y = Some(candidate);
+                }
+            }
+        }
+
+        (best_x.unwrap_or(x), best_y.unwrap_or(y))
+    }
+}
+
+impl canvas::Program<Message> for LayoutEditor {
+    type State = EditorState;
+
+    fn update(
+        &self,
+        state: &mut EditorState,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(position) = cursor.position_in(bounds) else {
+                    return (event::Status::Ignored, None);
+                };
+
+                let view = View::fit(&self.monitors, bounds.size());
+                // Later monitors are drawn on top, so they win
+                let hit = self
+                    .monitors
+                    .iter()
+                    .rposition(|monitor| view.rectangle(monitor).contains(position));
+
+                match hit {
+                    Some(index) => {
+                        let (x, y) = self.monitors[index].position;
+                        let grab = view.to_logical(position) - Point::new(x as f32, y as f32);
+                        state.drag = Some(Drag { index, grab, view });
+                        (event::Status::Captured, None)
+                    }
+                    None => (event::Status::Ignored, None),
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let (Some(drag), Some(position)) =
+                    (&state.drag, cursor.position_from(bounds.position()))
+                else {
+                    return (event::Status::Ignored, None);
+                };
+
+                let top_left = drag.view.to_logical(position) - drag.grab;
+                let moved = self.snap(
+                    drag.index,
+                    (top_left.x.round() as i32, top_left.y.round() as i32),
+                    drag.view.scale,
+                );
+
+                if moved == self.monitors[drag.index].position {
+                    return (event::Status::Captured, None);
+                }
+                (
+                    event::Status::Captured,
+                    Some(Message::MoveMonitorTo(drag.index, moved.0, moved.1)),
+                )
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.drag.take().is_some() {
+                    (event::Status::Captured, None)
+                } else {
+                    (event::Status::Ignored, None)
+                }
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &EditorState,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let view = state
+            .drag
+            .as_ref()
+            .map(|drag| drag.view)
+            .unwrap_or_else(|| View::fit(&self.monitors, bounds.size()));
+
+        frame.fill_rectangle(
+            Point::ORIGIN,
+            bounds.size(),
+            Color::from_rgba(0.05, 0.05, 0.05, 0.6),
+        );
+
+        for (index, monitor) in self.monitors.iter().enumerate() {
+            let rectangle = view.rectangle(monitor);
+            let dragging = state.drag.as_ref().is_some_and(|drag| drag.index == index);
+
+            let fill = if dragging {
+                Color::from_rgba(0.3, 0.5, 0.8, 0.8)
+            } else if monitor.primary {
+                Color::from_rgba(0.25, 0.4, 0.6, 0.7)
+            } else {
+                Color::from_rgba(0.25, 0.25, 0.3, 0.7)
+            };
+            frame.fill_rectangle(rectangle.position(), rectangle.size(), fill);
+            frame.stroke(
+                &Path::rectangle(rectangle.position(), rectangle.size()),
+                Stroke::default()
+                    .with_color(Color::from_rgba(0.7, 0.8, 1.0, 0.9))
+                    .with_width(1.5),
+            );
+
+            frame.fill_text(Text {
+                content: format!("{}\n{}", monitor.name, monitor.resolution),
+                position: rectangle.center(),
+                color: Color::WHITE,
+                size: 12.0.into(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                vertical_alignment: alignment::Vertical::Center,
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &EditorState,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.drag.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+
+        let view = View::fit(&self.monitors, bounds.size());
+        match cursor.position_in(bounds) {
+            Some(position)
+                if self
+                    .monitors
+                    .iter()
+                    .any(|monitor| view.rectangle(monitor).contains(position)) =>
+            {
+                mouse::Interaction::Grab
+            }
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
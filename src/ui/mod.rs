@@ -1,5 +1,7 @@
 pub mod components;
+pub mod layout_editor;
 pub mod styles;
 
 pub use components::*;
+pub use layout_editor::{EditorMonitor, LayoutEditor};
 pub use styles::*;
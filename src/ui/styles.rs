@@ -86,6 +86,10 @@ pub fn action_button_style_with_selection(
                 Color::from_rgba(0.3, 0.3, 0.2, 0.8),
                 Color::from_rgba(0.4, 0.4, 0.3, 0.6),
             ),
+            ActionButtonType::Profiles => (
+                Color::from_rgba(0.2, 0.25, 0.3, 0.8),
+                Color::from_rgba(0.3, 0.4, 0.5, 0.6),
+            ),
         };
 
         let (background_color, border_color, border_width) = if is_selected {
@@ -105,10 +109,12 @@ pub fn action_button_style_with_selection(
                 button::Status::Hovered => match button_type {
                     ActionButtonType::Cancel => Color::from_rgba(0.7, 0.2, 0.2, 0.8),
                     ActionButtonType::Reset => Color::from_rgba(0.6, 0.4, 0.2, 0.8),
+                    ActionButtonType::Profiles => Color::from_rgba(0.2, 0.4, 0.6, 0.8),
                 },
                 button::Status::Pressed => match button_type {
                     ActionButtonType::Cancel => Color::from_rgba(0.65, 0.15, 0.15, 0.9),
                     ActionButtonType::Reset => Color::from_rgba(0.55, 0.35, 0.15, 0.9),
+                    ActionButtonType::Profiles => Color::from_rgba(0.15, 0.35, 0.55, 0.9),
                 },
                 _ => base_bg,
             };
@@ -116,10 +122,12 @@ pub fn action_button_style_with_selection(
                 button::Status::Hovered => match button_type {
                     ActionButtonType::Cancel => Color::from_rgba(0.8, 0.3, 0.3, 0.8),
                     ActionButtonType::Reset => Color::from_rgba(0.7, 0.5, 0.3, 0.8),
+                    ActionButtonType::Profiles => Color::from_rgba(0.3, 0.5, 0.7, 0.8),
                 },
                 button::Status::Pressed => match button_type {
                     ActionButtonType::Cancel => Color::from_rgba(0.75, 0.25, 0.25, 0.9),
                     ActionButtonType::Reset => Color::from_rgba(0.65, 0.45, 0.25, 0.9),
+                    ActionButtonType::Profiles => Color::from_rgba(0.25, 0.45, 0.65, 0.9),
                 },
                 _ => base_border,
             };
@@ -147,6 +155,7 @@ pub fn action_button_style_with_selection(
 pub enum ActionButtonType {
     Cancel,
     Reset,
+    Profiles,
 }
 
 pub fn card_button_style() -> impl Fn(&Theme, button::Status) -> button::Style {